@@ -0,0 +1,162 @@
+//! Derive macros for `snowflake-api`, use them through its `derive` feature.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, Index, LitStr};
+
+/// Implements `snowflake_api::bindings::ToBindings` for a struct.
+///
+/// Parameters are keyed by field position starting from `"1"`, in declaration order,
+/// which matches `?` and `:N` placeholders. Every field has to implement `ToSql`,
+/// `Option` fields bind `None` as NULL.
+#[proc_macro_derive(ToBindings)]
+pub fn derive_to_bindings(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    to_bindings(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn to_bindings(input: &DeriveInput) -> Result<proc_macro2::TokenStream, Error> {
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new_spanned(
+            input,
+            "ToBindings can only be derived for structs",
+        ));
+    };
+
+    let accessors = match &data.fields {
+        Fields::Named(fields) => fields
+            .named
+            .iter()
+            .map(|f| {
+                let ident = f.ident.as_ref().unwrap();
+                quote!(#ident)
+            })
+            .collect(),
+        Fields::Unnamed(fields) => (0..fields.unnamed.len())
+            .map(|i| {
+                let index = Index::from(i);
+                quote!(#index)
+            })
+            .collect(),
+        Fields::Unit => Vec::new(),
+    };
+
+    let inserts = accessors.iter().enumerate().map(|(i, accessor)| {
+        let key = (i + 1).to_string();
+        // errors name the field rather than the position, which is easier to find in the struct
+        let field = accessor.to_string();
+        quote! {
+            bindings.insert(
+                #key.to_string(),
+                ::snowflake_api::ParameterBinding::try_from(
+                    &self.#accessor as &dyn ::snowflake_api::bindings::ToSql,
+                )
+                .map_err(|e| e.for_parameter(#field))?,
+            );
+        }
+    });
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::snowflake_api::bindings::ToBindings for #name #ty_generics #where_clause {
+            fn to_bindings(
+                &self,
+            ) -> ::std::result::Result<::snowflake_api::Bindings, ::snowflake_api::bindings::BindingError> {
+                #[allow(unused_mut)]
+                let mut bindings = ::snowflake_api::Bindings::new();
+                #(#inserts)*
+                Ok(bindings)
+            }
+        }
+    })
+}
+
+/// Implements `snowflake_api::bindings::ToSql` for an enum without fields, bound as `TEXT`.
+///
+/// Variants are bound as their name, eg `Status::Active` as `Active`,
+/// unless renamed with `#[snowflake(rename = "...")]`.
+#[proc_macro_derive(ToSql, attributes(snowflake))]
+pub fn derive_to_sql(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    to_sql(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn to_sql(input: &DeriveInput) -> Result<proc_macro2::TokenStream, Error> {
+    let Data::Enum(data) = &input.data else {
+        return Err(Error::new_spanned(
+            input,
+            "ToSql can only be derived for enums",
+        ));
+    };
+
+    let arms = data
+        .variants
+        .iter()
+        .map(|variant| {
+            if !matches!(variant.fields, Fields::Unit) {
+                return Err(Error::new_spanned(
+                    variant,
+                    "ToSql can only be derived for variants without fields",
+                ));
+            }
+
+            let mut text = variant.ident.to_string();
+            for attr in variant
+                .attrs
+                .iter()
+                .filter(|a| a.path().is_ident("snowflake"))
+            {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("rename") {
+                        text = meta.value()?.parse::<LitStr>()?.value();
+                        Ok(())
+                    } else {
+                        Err(meta.error("unsupported attribute, expected `rename`"))
+                    }
+                })?;
+            }
+
+            let ident = &variant.ident;
+            Ok(quote!(Self::#ident => #text,))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::snowflake_api::bindings::ToSql for #name #ty_generics #where_clause {
+            fn sql_type(&self) -> ::snowflake_api::SnowflakeType {
+                ::snowflake_api::SnowflakeType::Text
+            }
+
+            fn null_sql_type() -> ::snowflake_api::SnowflakeType {
+                ::snowflake_api::SnowflakeType::Text
+            }
+
+            fn to_sql(
+                &self,
+                out: &mut ::snowflake_api::bindings::BytesMut,
+            ) -> ::std::result::Result<
+                ::snowflake_api::bindings::IsNull,
+                ::snowflake_api::bindings::BindingError,
+            > {
+                let text: &str = match *self {
+                    #(#arms)*
+                };
+                out.extend_from_slice(text.as_bytes());
+                Ok(::snowflake_api::bindings::IsNull::No)
+            }
+
+            fn encode_format(&self) -> &'static str {
+                ""
+            }
+        }
+    })
+}
@@ -0,0 +1,231 @@
+//! Conversion of Snowflake Arrow encodings into logical Arrow types.
+//!
+//! Snowflake doesn't use Arrow temporal and decimal types in result sets, instead it describes
+//! columns with field metadata: `NUMBER(p, s)` is an integer of unscaled values, timestamps are
+//! either scaled integers or structs of epoch seconds and nanoseconds.
+//!
+//! ```rust
+//! use std::collections::HashMap;
+//! use std::sync::Arc;
+//!
+//! use arrow::array::{Array, Int64Array, StringArray};
+//! use arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+//! use arrow::record_batch::RecordBatch;
+//! use snowflake_api::arrow_types::to_logical_types;
+//!
+//! let metadata = |logical_type: &str, scale: &str| {
+//!     HashMap::from([
+//!         ("logicalType".to_owned(), logical_type.to_owned()),
+//!         ("precision".to_owned(), "10".to_owned()),
+//!         ("scale".to_owned(), scale.to_owned()),
+//!     ])
+//! };
+//! let schema = Schema::new(vec![
+//!     Field::new("PRICE", DataType::Int64, true).with_metadata(metadata("FIXED", "2")),
+//!     Field::new("NAME", DataType::Utf8, true).with_metadata(metadata("TEXT", "0")),
+//!     Field::new("CREATED", DataType::Int64, true).with_metadata(metadata("TIMESTAMP_NTZ", "3")),
+//! ]);
+//! let batch = RecordBatch::try_new(
+//!     Arc::new(schema),
+//!     vec![
+//!         Arc::new(Int64Array::from(vec![150])),
+//!         Arc::new(StringArray::from(vec!["apple"])),
+//!         Arc::new(Int64Array::from(vec![1_700_000_000_123])),
+//!     ],
+//! )
+//! .unwrap();
+//!
+//! let batch = to_logical_types(&batch).unwrap();
+//! assert_eq!(batch.column(0).data_type(), &DataType::Decimal128(10, 2));
+//! assert_eq!(batch.column(1).data_type(), &DataType::Utf8);
+//! assert_eq!(
+//!     batch.column(2).data_type(),
+//!     &DataType::Timestamp(TimeUnit::Nanosecond, None)
+//! );
+//! ```
+
+use std::sync::Arc;
+
+use arrow::array::{
+    Array, ArrayRef, AsArray, Decimal128Array, StructArray, Time64NanosecondArray,
+    TimestampNanosecondArray,
+};
+use arrow::compute::cast;
+use arrow::datatypes::{DataType, Field, Int32Type, Int64Type, Schema};
+use arrow::error::ArrowError;
+use arrow::record_batch::RecordBatch;
+use arrow::util::display::array_value_to_string;
+
+/// Scale of nanoseconds, the unit all temporal columns are converted to
+const NANOS_SCALE: u32 = 9;
+
+/// Converts columns of the batch from Snowflake encodings into matching Arrow types:
+///
+/// * `FIXED` with non-zero scale becomes `Decimal128(precision, scale)`
+/// * `TIME` becomes `Time64(Nanosecond)`
+/// * `TIMESTAMP_NTZ` becomes `Timestamp(Nanosecond, None)`
+/// * `TIMESTAMP_LTZ` and `TIMESTAMP_TZ` become `Timestamp(Nanosecond, "UTC")`,
+///   the original offset of `TIMESTAMP_TZ` values is not preserved
+///
+/// Other columns are returned as-is.
+pub fn to_logical_types(batch: &RecordBatch) -> Result<RecordBatch, ArrowError> {
+    let schema = batch.schema();
+    let (fields, columns): (Vec<_>, Vec<_>) = schema
+        .fields()
+        .iter()
+        .zip(batch.columns())
+        .map(|(field, column)| convert_column(field, column))
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .unzip();
+
+    let schema = Schema::new_with_metadata(fields, schema.metadata().clone());
+    RecordBatch::try_new(Arc::new(schema), columns)
+}
+
+fn convert_column(field: &Field, column: &ArrayRef) -> Result<(Field, ArrayRef), ArrowError> {
+    let metadata = field.metadata();
+    let logical_type = metadata.get("logicalType").map(String::as_str);
+    let scale = metadata
+        .get("scale")
+        .and_then(|s| s.parse::<u32>().ok())
+        .unwrap_or(0);
+
+    let converted: ArrayRef = match (logical_type, column.data_type()) {
+        (Some("FIXED"), dt) if scale > 0 && dt.is_integer() => {
+            let precision = metadata
+                .get("precision")
+                .and_then(|p| p.parse::<u8>().ok())
+                .unwrap_or(38);
+            let values = cast(column, &DataType::Int64)?;
+            let decimals: Decimal128Array = values
+                .as_primitive::<Int64Type>()
+                .iter()
+                .map(|v| v.map(i128::from))
+                .collect();
+            let scale = i8::try_from(scale)
+                .map_err(|_| ArrowError::SchemaError(format!("Invalid decimal scale {scale}")))?;
+            Arc::new(decimals.with_precision_and_scale(precision, scale)?)
+        }
+        (Some("TIME"), dt) if dt.is_integer() => {
+            let values = cast(column, &DataType::Int64)?;
+            let times: Time64NanosecondArray = values
+                .as_primitive::<Int64Type>()
+                .iter()
+                .map(|v| v.map(|v| scaled_to_nanos(v, scale)).transpose())
+                .collect::<Result<_, _>>()?;
+            Arc::new(times)
+        }
+        (Some(ty @ ("TIMESTAMP_NTZ" | "TIMESTAMP_LTZ" | "TIMESTAMP_TZ")), DataType::Int64) => {
+            let timestamps: TimestampNanosecondArray = column
+                .as_primitive::<Int64Type>()
+                .iter()
+                .map(|v| v.map(|v| scaled_to_nanos(v, scale)).transpose())
+                .collect::<Result<_, _>>()?;
+            Arc::new(timestamps.with_timezone_opt(timezone(ty)))
+        }
+        (Some(ty @ ("TIMESTAMP_NTZ" | "TIMESTAMP_LTZ" | "TIMESTAMP_TZ")), DataType::Struct(_)) => {
+            let timestamps = struct_to_nanos(column.as_struct(), scale)?;
+            Arc::new(timestamps.with_timezone_opt(timezone(ty)))
+        }
+        _ => return Ok((field.clone(), Arc::clone(column))),
+    };
+
+    let field = Field::new(
+        field.name(),
+        converted.data_type().clone(),
+        field.is_nullable(),
+    )
+    .with_metadata(metadata.clone());
+    Ok((field, converted))
+}
+
+fn timezone(logical_type: &str) -> Option<&'static str> {
+    if logical_type == "TIMESTAMP_NTZ" {
+        None
+    } else {
+        Some("UTC")
+    }
+}
+
+/// Timestamps are encoded either as `{epoch, fraction[, timezone]}`, where `epoch` is in seconds
+/// and `fraction` in nanoseconds, or as `{epoch, timezone}`, where `epoch` is scaled by column scale.
+/// `timezone` is an offset and is not needed to get the instant.
+fn struct_to_nanos(
+    array: &StructArray,
+    scale: u32,
+) -> Result<TimestampNanosecondArray, ArrowError> {
+    let missing = |name| ArrowError::SchemaError(format!("Timestamp struct is missing `{name}`"));
+
+    let epoch = cast(
+        array
+            .column_by_name("epoch")
+            .ok_or_else(|| missing("epoch"))?,
+        &DataType::Int64,
+    )?;
+    let epoch = epoch.as_primitive::<Int64Type>();
+    let fraction = array
+        .column_by_name("fraction")
+        .map(|f| cast(f, &DataType::Int32))
+        .transpose()?;
+    let fraction = fraction.as_ref().map(AsArray::as_primitive::<Int32Type>);
+
+    (0..array.len())
+        .map(|i| {
+            if array.is_null(i) || epoch.is_null(i) {
+                return Ok(None);
+            }
+            let nanos = match fraction {
+                Some(fraction) => scaled_to_nanos(epoch.value(i), 0)?
+                    .checked_add(i64::from(fraction.value(i)))
+                    .ok_or_else(out_of_range)?,
+                None => scaled_to_nanos(epoch.value(i), scale)?,
+            };
+            Ok(Some(nanos))
+        })
+        .collect()
+}
+
+fn scaled_to_nanos(value: i64, scale: u32) -> Result<i64, ArrowError> {
+    let multiplier = 10_i64.pow(NANOS_SCALE.saturating_sub(scale));
+    value.checked_mul(multiplier).ok_or_else(out_of_range)
+}
+
+fn out_of_range() -> ArrowError {
+    ArrowError::ComputeError("Temporal value is out of nanosecond precision range".to_owned())
+}
+
+/// Column names and rows of values in their text representation, NULLs are `None`
+pub(crate) type TextRows = (Vec<String>, Vec<Vec<Option<String>>>);
+
+pub(crate) fn text_rows(batches: &[RecordBatch]) -> Result<TextRows, ArrowError> {
+    let columns = batches
+        .first()
+        .map(|b| {
+            b.schema()
+                .fields()
+                .iter()
+                .map(|f| f.name().clone())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut rows = Vec::new();
+    for batch in batches {
+        for row in 0..batch.num_rows() {
+            let values = batch
+                .columns()
+                .iter()
+                .map(|array| {
+                    if array.is_null(row) {
+                        Ok(None)
+                    } else {
+                        array_value_to_string(array, row).map(Some)
+                    }
+                })
+                .collect::<Result<_, _>>()?;
+            rows.push(values);
+        }
+    }
+    Ok((columns, rows))
+}
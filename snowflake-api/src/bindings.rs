@@ -0,0 +1,2656 @@
+//! Conversion of Rust values into Snowflake parameter bindings.
+//!
+//! Values are sent to Snowflake in their text form alongside the type they should be
+//! interpreted as, see [`ParameterBinding`].
+//!
+//! ```rust
+//! use snowflake_api::bindings::ToSql;
+//! use snowflake_api::{BindingValue, ParameterBinding, SnowflakeType};
+//!
+//! let value: Box<dyn ToSql> = Box::new(true);
+//! let binding = ParameterBinding::try_from(value).unwrap();
+//! assert_eq!(binding.type_, Some(SnowflakeType::Boolean));
+//! assert_eq!(binding.value, Some(BindingValue::SingleBind("true".to_string())));
+//! assert_eq!(binding.fmt, None);
+//!
+//! let value: Box<dyn ToSql> = Box::new(false);
+//! let binding = ParameterBinding::try_from(value).unwrap();
+//! assert_eq!(binding.type_, Some(SnowflakeType::Boolean));
+//! assert_eq!(binding.value, Some(BindingValue::SingleBind("false".to_string())));
+//! ```
+//!
+//! Semi-structured values also tell Snowflake how they are encoded:
+//!
+//! ```rust
+//! use std::collections::BTreeMap;
+//!
+//! use snowflake_api::bindings::{Object, ToSql};
+//! use snowflake_api::ParameterBinding;
+//!
+//! let value: Box<dyn ToSql> = Box::new(Object::new(BTreeMap::from([("answer", 42)])));
+//! let binding = ParameterBinding::try_from(value).unwrap();
+//! assert_eq!(binding.fmt.as_deref(), Some("json"));
+//! ```
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::hash::BuildHasher;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use base64::prelude::{Engine as _, BASE64_STANDARD};
+use bytes::BufMut;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::requests::{BindingValue, Bindings, ParameterBinding};
+use crate::responses::SnowflakeType;
+
+/// ```rust
+/// use snowflake_api::bindings::ToBindings;
+/// use snowflake_api::{BindingValue, SnowflakeType};
+///
+/// #[derive(ToBindings)]
+/// struct NewUser {
+///     name: String,
+///     age: i64,
+///     email: Option<String>,
+/// }
+///
+/// let user = NewUser {
+///     name: "Ferris".to_string(),
+///     age: 9,
+///     email: None,
+/// };
+/// let bindings = user.to_bindings().unwrap();
+/// assert_eq!(bindings["2"].type_, Some(SnowflakeType::Fixed));
+/// assert_eq!(bindings["2"].value, Some(BindingValue::SingleBind("9".to_string())));
+/// // NULLs keep the type of the field
+/// assert_eq!(bindings["3"].type_, Some(SnowflakeType::Text));
+/// assert_eq!(bindings["3"].value, None);
+/// ```
+///
+/// Errors name the field which failed to bind, see [`BindingError::Parameter`].
+#[cfg(feature = "derive")]
+pub use snowflake_derive::ToBindings;
+
+/// ```rust
+/// use snowflake_api::bindings::ToSql;
+/// use snowflake_api::{BindingValue, ParameterBinding, SnowflakeType};
+///
+/// #[derive(ToSql, Clone, Copy)]
+/// enum OrderStatus {
+///     Pending,
+///     Shipped,
+///     #[snowflake(rename = "CANCELLED_BY_USER")]
+///     Cancelled,
+/// }
+///
+/// let bound = |status: OrderStatus| ParameterBinding::from_value(status).unwrap();
+/// assert_eq!(bound(OrderStatus::Pending).type_, Some(SnowflakeType::Text));
+/// assert_eq!(
+///     bound(OrderStatus::Pending).value,
+///     Some(BindingValue::SingleBind("Pending".to_owned()))
+/// );
+/// assert_eq!(
+///     bound(OrderStatus::Shipped).value,
+///     Some(BindingValue::SingleBind("Shipped".to_owned()))
+/// );
+/// assert_eq!(
+///     bound(OrderStatus::Cancelled).value,
+///     Some(BindingValue::SingleBind("CANCELLED_BY_USER".to_owned()))
+/// );
+///
+/// // NULLs are still `TEXT`
+/// let binding = ParameterBinding::from_value(None::<OrderStatus>).unwrap();
+/// assert_eq!(binding.type_, Some(SnowflakeType::Text));
+/// ```
+#[cfg(feature = "derive")]
+pub use snowflake_derive::ToSql;
+
+/// Buffer the text of values is written into, see [`ToSql::to_sql`]
+pub use bytes::BytesMut;
+
+#[derive(Error, Debug)]
+pub enum BindingError {
+    #[error(transparent)]
+    SerialisationError(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    Utf8EncodingError(#[from] std::string::FromUtf8Error),
+
+    #[error("Failed to write binding value")]
+    WriteError(#[from] std::fmt::Error),
+
+    #[error("Geospatial value must be a non-empty WKT string")]
+    EmptyWkt,
+
+    #[error("Path `{}` is not valid UTF-8", .0.display())]
+    NonUtf8Path(std::path::PathBuf),
+
+    #[error("Row {0} has {1} values, while {2} columns are inserted")]
+    RowLengthMismatch(usize, usize, usize),
+
+    #[error("Column `{0}` has values of different types: {1} and {2}")]
+    MixedColumnTypes(String, SnowflakeType, SnowflakeType),
+
+    #[error(transparent)]
+    #[cfg(feature = "time")]
+    TimeFormatError(#[from] time::error::Format),
+
+    #[error("Value `{0}` has non-zero digits beyond scale {1}")]
+    ScaleTruncation(String, u32),
+
+    #[error("Timestamp is too far from the UNIX epoch to be bound")]
+    TimestampOutOfRange,
+
+    /// Failure of a single parameter, with its name or position, eg `1`, or its struct field
+    ///
+    /// ```rust
+    /// use std::collections::HashMap;
+    ///
+    /// use serde::Serialize;
+    /// use snowflake_api::bindings::{BindingError, Object, ToBindings};
+    /// use snowflake_api::ExecRequestBuilder;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Grid {
+    ///     // JSON object keys must be strings
+    ///     cells: HashMap<(u8, u8), char>,
+    /// }
+    ///
+    /// let grid = || Object::new(Grid { cells: HashMap::from([((0, 0), 'x')]) });
+    ///
+    /// let err = ExecRequestBuilder::new().bind("grid", grid()).unwrap_err();
+    /// assert!(matches!(&err, BindingError::Parameter(name, _) if name == "grid"));
+    /// assert_eq!(
+    ///     err.to_string(),
+    ///     "Failed to bind parameter `grid`: key must be a string"
+    /// );
+    ///
+    /// let err = ("first", grid()).to_bindings().unwrap_err();
+    /// assert_eq!(err.to_string(), "Failed to bind parameter `2`: key must be a string");
+    /// ```
+    #[error("Failed to bind parameter `{0}`: {1}")]
+    Parameter(String, #[source] Box<BindingError>),
+}
+
+impl BindingError {
+    /// Attach the name of the parameter which failed to bind
+    #[must_use]
+    pub fn for_parameter(self, name: impl Into<String>) -> Self {
+        Self::Parameter(name.into(), Box::new(self))
+    }
+}
+
+/// Whether the serialized value should be sent as SQL NULL
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IsNull {
+    Yes,
+    No,
+}
+
+/// A value which can be bound as a query parameter
+pub trait ToSql {
+    /// Type which Snowflake should interpret the value as
+    fn sql_type(&self) -> SnowflakeType;
+
+    /// Type of NULL values, eg `None::<i64>` is still bound as a `NUMBER`
+    fn null_sql_type() -> SnowflakeType
+    where
+        Self: Sized,
+    {
+        SnowflakeType::UnknownNull
+    }
+
+    /// Writes text representation of the value into the buffer
+    fn to_sql(&self, out: &mut BytesMut) -> Result<IsNull, BindingError>;
+
+    /// Text representation of each element for values bound as an array,
+    /// see [`BindingValue::MultiBind`]. `None` for single values.
+    fn to_sql_multi(&self) -> Result<Option<Vec<Option<String>>>, BindingError> {
+        Ok(None)
+    }
+
+    /// Format hint for values which need it, eg `json` for semi-structured types.
+    /// Empty string means no hint is given.
+    fn encode_format(&self) -> &'static str;
+}
+
+macro_rules! default_encode {
+    () => {
+        fn encode_format(&self) -> &'static str {
+            ""
+        }
+    };
+}
+
+macro_rules! serializable_impl {
+    ($(#[$attr:meta])* [$t:ty $(, $rest:ty)*], $sql_type:expr) => {
+        serializable_impl!($(#[$attr])* $t, $sql_type);
+        serializable_impl!($(#[$attr])* [$($rest),*], $sql_type);
+    };
+    ($(#[$attr:meta])* [], $sql_type:expr) => {};
+    ($(#[$attr:meta])* $t:ty, $sql_type:expr) => {
+        $(#[$attr])*
+        impl ToSql for $t {
+            fn sql_type(&self) -> SnowflakeType {
+                $sql_type
+            }
+
+            fn null_sql_type() -> SnowflakeType {
+                $sql_type
+            }
+
+            fn to_sql(&self, out: &mut BytesMut) -> Result<IsNull, BindingError> {
+                write!(out, "{self}")?;
+                Ok(IsNull::No)
+            }
+
+            default_encode!();
+        }
+    };
+}
+
+serializable_impl!(i8, SnowflakeType::Fixed);
+serializable_impl!(i16, SnowflakeType::Fixed);
+serializable_impl!(i32, SnowflakeType::Fixed);
+serializable_impl!(i64, SnowflakeType::Fixed);
+// values beyond NUMBER(38, 0) are rejected by the server, not truncated
+serializable_impl!(i128, SnowflakeType::Fixed);
+serializable_impl!(isize, SnowflakeType::Fixed);
+serializable_impl!(u8, SnowflakeType::Fixed);
+serializable_impl!(u16, SnowflakeType::Fixed);
+serializable_impl!(u32, SnowflakeType::Fixed);
+serializable_impl!(u64, SnowflakeType::Fixed);
+serializable_impl!(
+    /// Values above `NUMBER(38, 0)` are rejected by the server, `u128::MAX` has 39 digits
+    ///
+    /// ```rust
+    /// use snowflake_api::{BindingValue, ParameterBinding, SnowflakeType};
+    ///
+    /// let binding = ParameterBinding::from_value(u128::MAX).unwrap();
+    /// assert_eq!(binding.type_, Some(SnowflakeType::Fixed));
+    /// assert_eq!(
+    ///     binding.value,
+    ///     Some(BindingValue::SingleBind(
+    ///         "340282366920938463463374607431768211455".to_owned()
+    ///     ))
+    /// );
+    /// ```
+    u128,
+    SnowflakeType::Fixed
+);
+serializable_impl!(
+    /// Bound the same way as the fixed size integer of the target's pointer width
+    ///
+    /// ```rust
+    /// use snowflake_api::ParameterBinding;
+    ///
+    /// #[cfg(target_pointer_width = "64")]
+    /// assert_eq!(
+    ///     ParameterBinding::from_value(usize::MAX).unwrap(),
+    ///     ParameterBinding::from_value(u64::MAX).unwrap()
+    /// );
+    /// ```
+    usize,
+    SnowflakeType::Fixed
+);
+serializable_impl!(
+    /// Non-zero integers bind the same way as the underlying integers
+    [
+        std::num::NonZeroI8,
+        std::num::NonZeroI16,
+        std::num::NonZeroI32,
+        std::num::NonZeroI64,
+        std::num::NonZeroI128,
+        std::num::NonZeroIsize,
+        std::num::NonZeroU8,
+        std::num::NonZeroU16,
+        std::num::NonZeroU32,
+        std::num::NonZeroU128,
+        std::num::NonZeroUsize
+    ],
+    SnowflakeType::Fixed
+);
+serializable_impl!(
+    /// Non-zero integers bind the same way as the underlying integers
+    ///
+    /// ```rust
+    /// use std::num::NonZeroU64;
+    ///
+    /// use snowflake_api::bindings::ToSql;
+    /// use snowflake_api::ParameterBinding;
+    ///
+    /// let value = NonZeroU64::new(42).unwrap();
+    /// let binding = ParameterBinding::try_from(&value as &dyn ToSql).unwrap();
+    /// assert_eq!(binding, ParameterBinding::try_from(&42u64 as &dyn ToSql).unwrap());
+    /// ```
+    std::num::NonZeroU64,
+    SnowflakeType::Fixed
+);
+
+serializable_impl!(
+    /// Written the same way as `f64`, in the shortest round-trip representation,
+    /// non-finite values as `NaN`, `inf` and `-inf`
+    ///
+    /// ```rust
+    /// use snowflake_api::{BindingValue, ParameterBinding, SnowflakeType};
+    ///
+    /// let binding = ParameterBinding::from_value(0.1f32).unwrap();
+    /// assert_eq!(binding.type_, Some(SnowflakeType::Real));
+    /// assert_eq!(binding.value, Some(BindingValue::SingleBind("0.1".to_owned())));
+    ///
+    /// for (value, text) in [(f32::NAN, "NaN"), (f32::INFINITY, "inf"), (f32::NEG_INFINITY, "-inf")] {
+    ///     let binding = ParameterBinding::from_value(value).unwrap();
+    ///     assert_eq!(binding.value, Some(BindingValue::SingleBind(text.to_owned())));
+    /// }
+    /// ```
+    f32,
+    SnowflakeType::Real
+);
+serializable_impl!(
+    /// Floats are written with `Display`, in their shortest round-trip representation.
+    /// Its text of non-finite values, `NaN`, `inf` and `-inf`, is what Snowflake accepts
+    /// for the `FLOAT` special values.
+    ///
+    /// ```rust
+    /// use snowflake_api::bindings::ToSql;
+    /// use snowflake_api::{BindingValue, ParameterBinding};
+    ///
+    /// for (value, text) in [(f64::NAN, "NaN"), (f64::INFINITY, "inf"), (f64::NEG_INFINITY, "-inf")] {
+    ///     let value: Box<dyn ToSql> = Box::new(value);
+    ///     let binding = ParameterBinding::try_from(value).unwrap();
+    ///     assert_eq!(binding.value, Some(BindingValue::SingleBind(text.to_owned())));
+    /// }
+    /// ```
+    f64,
+    SnowflakeType::Real
+);
+
+serializable_impl!(
+    /// IP addresses are bound as `VARCHAR` in their standard notation,
+    /// IPv6 addresses are written in the compressed form of RFC 5952
+    ///
+    /// ```rust
+    /// use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+    ///
+    /// use snowflake_api::bindings::ToSql;
+    /// use snowflake_api::{BindingValue, ParameterBinding, SnowflakeType};
+    ///
+    /// let bind = |value: &dyn ToSql| ParameterBinding::try_from(value).unwrap();
+    /// let text = |value: &str| Some(BindingValue::SingleBind(value.to_owned()));
+    ///
+    /// let binding = bind(&Ipv4Addr::new(192, 168, 0, 1));
+    /// assert_eq!(binding.type_, Some(SnowflakeType::Text));
+    /// assert_eq!(binding.value, text("192.168.0.1"));
+    ///
+    /// let full = Ipv6Addr::new(0x2001, 0xdb8, 0x85a3, 0x1, 0x2, 0x8a2e, 0x370, 0x7334);
+    /// assert_eq!(bind(&full).value, text("2001:db8:85a3:1:2:8a2e:370:7334"));
+    ///
+    /// let compressed: IpAddr = "2001:0db8:0000:0000:0000:0000:0000:0001".parse().unwrap();
+    /// assert_eq!(bind(&compressed).value, text("2001:db8::1"));
+    /// ```
+    std::net::IpAddr,
+    SnowflakeType::Text
+);
+serializable_impl!(
+    /// Bound as `VARCHAR` in dotted decimal notation
+    std::net::Ipv4Addr,
+    SnowflakeType::Text
+);
+serializable_impl!(
+    /// Bound as `VARCHAR` in the compressed form of RFC 5952
+    std::net::Ipv6Addr,
+    SnowflakeType::Text
+);
+
+impl ToSql for bool {
+    fn sql_type(&self) -> SnowflakeType {
+        SnowflakeType::Boolean
+    }
+
+    fn null_sql_type() -> SnowflakeType {
+        SnowflakeType::Boolean
+    }
+
+    fn to_sql(&self, out: &mut BytesMut) -> Result<IsNull, BindingError> {
+        // Snowflake parses boolean literals case-insensitively, lowercase matches its output
+        out.write_str(if *self { "true" } else { "false" })?;
+        Ok(IsNull::No)
+    }
+
+    default_encode!();
+}
+
+impl ToSql for &str {
+    fn sql_type(&self) -> SnowflakeType {
+        SnowflakeType::Text
+    }
+
+    fn null_sql_type() -> SnowflakeType {
+        SnowflakeType::Text
+    }
+
+    fn to_sql(&self, out: &mut BytesMut) -> Result<IsNull, BindingError> {
+        out.write_str(self)?;
+        Ok(IsNull::No)
+    }
+
+    default_encode!();
+}
+
+impl ToSql for String {
+    fn sql_type(&self) -> SnowflakeType {
+        SnowflakeType::Text
+    }
+
+    fn null_sql_type() -> SnowflakeType {
+        SnowflakeType::Text
+    }
+
+    fn to_sql(&self, out: &mut BytesMut) -> Result<IsNull, BindingError> {
+        out.write_str(self)?;
+        Ok(IsNull::No)
+    }
+
+    default_encode!();
+}
+
+/// ```rust
+/// use std::borrow::Cow;
+///
+/// use snowflake_api::{BindingValue, ExecRequestBuilder, SnowflakeType};
+///
+/// let request = ExecRequestBuilder::new()
+///     .bind("1", Cow::Borrowed("borrowed"))
+///     .unwrap()
+///     .bind("2", Cow::<str>::Owned("owned".to_owned()))
+///     .unwrap();
+/// let bindings = request.bindings();
+/// assert_eq!(bindings["1"].type_, Some(SnowflakeType::Text));
+/// assert_eq!(bindings["1"].value, Some(BindingValue::SingleBind("borrowed".to_owned())));
+/// assert_eq!(bindings["2"].value, Some(BindingValue::SingleBind("owned".to_owned())));
+/// ```
+impl ToSql for Cow<'_, str> {
+    fn sql_type(&self) -> SnowflakeType {
+        SnowflakeType::Text
+    }
+
+    fn null_sql_type() -> SnowflakeType {
+        SnowflakeType::Text
+    }
+
+    fn to_sql(&self, out: &mut BytesMut) -> Result<IsNull, BindingError> {
+        self.as_ref().to_sql(out)
+    }
+
+    default_encode!();
+}
+
+/// Bound as `TEXT` of the UTF-8 encoded character, 1 to 4 bytes
+///
+/// ```rust
+/// use snowflake_api::bindings::{BytesMut, IsNull, ToSql};
+///
+/// for c in ['a', 'é', '€', '𝄞', '🦀'] {
+///     let mut out = BytesMut::new();
+///     assert!(matches!(c.to_sql(&mut out).unwrap(), IsNull::No));
+///     assert_eq!(&out[..], c.to_string().as_bytes());
+/// }
+///
+/// let mut out = BytesMut::new();
+/// '\u{1D11E}'.to_sql(&mut out).unwrap();
+/// assert_eq!(&out[..], [0xF0, 0x9D, 0x84, 0x9E]);
+/// assert_eq!(std::str::from_utf8(&out).unwrap(), "𝄞");
+/// ```
+impl ToSql for char {
+    fn sql_type(&self) -> SnowflakeType {
+        SnowflakeType::Text
+    }
+
+    fn null_sql_type() -> SnowflakeType {
+        SnowflakeType::Text
+    }
+
+    fn to_sql(&self, out: &mut BytesMut) -> Result<IsNull, BindingError> {
+        out.extend_from_slice(self.encode_utf8(&mut [0; 4]).as_bytes());
+        Ok(IsNull::No)
+    }
+
+    default_encode!();
+}
+
+/// Paths are bound as `TEXT`, eg for stage locations or file names.
+/// Paths which are not valid UTF-8 fail with [`BindingError::NonUtf8Path`],
+/// rather than being sent with replacement characters.
+///
+/// ```rust
+/// use std::path::{Path, PathBuf};
+///
+/// use snowflake_api::{BindingValue, ExecRequestBuilder, SnowflakeType};
+///
+/// let request = ExecRequestBuilder::new()
+///     .bind("1", Path::new("/tmp/data/orders.csv"))
+///     .unwrap()
+///     .bind("2", PathBuf::from("orders").join("2024.csv"))
+///     .unwrap();
+/// let bindings = request.bindings();
+/// assert_eq!(bindings["1"].type_, Some(SnowflakeType::Text));
+/// assert_eq!(
+///     bindings["1"].value,
+///     Some(BindingValue::SingleBind("/tmp/data/orders.csv".to_owned()))
+/// );
+/// # #[cfg(unix)]
+/// assert_eq!(
+///     bindings["2"].value,
+///     Some(BindingValue::SingleBind("orders/2024.csv".to_owned()))
+/// );
+/// ```
+///
+/// ```rust
+/// # #[cfg(unix)]
+/// # {
+/// use std::ffi::OsStr;
+/// use std::os::unix::ffi::OsStrExt;
+/// use std::path::Path;
+///
+/// use snowflake_api::bindings::BindingError;
+/// use snowflake_api::ExecRequestBuilder;
+///
+/// let path = Path::new(OsStr::from_bytes(b"/tmp/\xff.csv"));
+/// let result = ExecRequestBuilder::new().bind("1", path);
+/// let Err(BindingError::Parameter(name, source)) = result else {
+///     panic!("expected a parameter error");
+/// };
+/// assert_eq!(name, "1");
+/// assert!(matches!(*source, BindingError::NonUtf8Path(p) if p == path));
+/// # }
+/// ```
+impl ToSql for &std::path::Path {
+    fn sql_type(&self) -> SnowflakeType {
+        SnowflakeType::Text
+    }
+
+    fn null_sql_type() -> SnowflakeType {
+        SnowflakeType::Text
+    }
+
+    fn to_sql(&self, out: &mut BytesMut) -> Result<IsNull, BindingError> {
+        let path = self
+            .to_str()
+            .ok_or_else(|| BindingError::NonUtf8Path(self.to_path_buf()))?;
+        out.write_str(path)?;
+        Ok(IsNull::No)
+    }
+
+    default_encode!();
+}
+
+impl ToSql for std::path::PathBuf {
+    fn sql_type(&self) -> SnowflakeType {
+        SnowflakeType::Text
+    }
+
+    fn null_sql_type() -> SnowflakeType {
+        SnowflakeType::Text
+    }
+
+    fn to_sql(&self, out: &mut BytesMut) -> Result<IsNull, BindingError> {
+        self.as_path().to_sql(out)
+    }
+
+    default_encode!();
+}
+
+/// Binary values are hex-encoded, which is Snowflake's default `BINARY_INPUT_FORMAT`,
+/// see [`Binary`] for base64
+impl ToSql for &[u8] {
+    fn sql_type(&self) -> SnowflakeType {
+        SnowflakeType::Binary
+    }
+
+    fn null_sql_type() -> SnowflakeType {
+        SnowflakeType::Binary
+    }
+
+    fn to_sql(&self, out: &mut BytesMut) -> Result<IsNull, BindingError> {
+        for byte in *self {
+            write!(out, "{byte:02x}")?;
+        }
+        Ok(IsNull::No)
+    }
+
+    fn encode_format(&self) -> &'static str {
+        "hex"
+    }
+}
+
+impl ToSql for Vec<u8> {
+    fn sql_type(&self) -> SnowflakeType {
+        SnowflakeType::Binary
+    }
+
+    fn null_sql_type() -> SnowflakeType {
+        SnowflakeType::Binary
+    }
+
+    fn to_sql(&self, out: &mut BytesMut) -> Result<IsNull, BindingError> {
+        self.as_slice().to_sql(out)
+    }
+
+    fn encode_format(&self) -> &'static str {
+        "hex"
+    }
+}
+
+/// Text encoding of [`Binary`] values, sent along with the value so it doesn't depend on
+/// the `BINARY_INPUT_FORMAT` of the session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BinaryFormat {
+    /// Lower case hex digits, the same as plain byte slices
+    #[default]
+    Hex,
+    /// Standard base64 alphabet with padding
+    Base64,
+}
+
+impl BinaryFormat {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Hex => "hex",
+            Self::Base64 => "base64",
+        }
+    }
+}
+
+/// Bytes bound as `BINARY` in the chosen encoding, eg base64 for a shorter request body
+///
+/// ```rust
+/// use snowflake_api::bindings::{Binary, BinaryFormat};
+/// use snowflake_api::{BindingValue, ParameterBinding, SnowflakeType};
+///
+/// let data = vec![0xde, 0xad, 0xbe, 0xef, 0x00];
+///
+/// let hex = ParameterBinding::from_value(Binary::new(data.clone(), BinaryFormat::Hex)).unwrap();
+/// assert_eq!(hex.type_, Some(SnowflakeType::Binary));
+/// assert_eq!(hex.value, Some(BindingValue::SingleBind("deadbeef00".to_owned())));
+/// assert_eq!(hex.fmt.as_deref(), Some("hex"));
+/// assert_eq!(hex, ParameterBinding::from_value(data.clone()).unwrap());
+///
+/// let base64 = ParameterBinding::from_value(Binary::new(data, BinaryFormat::Base64)).unwrap();
+/// assert_eq!(base64.type_, Some(SnowflakeType::Binary));
+/// assert_eq!(base64.value, Some(BindingValue::SingleBind("3q2+7wA=".to_owned())));
+/// assert_eq!(base64.fmt.as_deref(), Some("base64"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Binary {
+    pub data: Vec<u8>,
+    pub format: BinaryFormat,
+}
+
+impl Binary {
+    pub fn new(data: impl Into<Vec<u8>>, format: BinaryFormat) -> Self {
+        Self {
+            data: data.into(),
+            format,
+        }
+    }
+}
+
+impl ToSql for Binary {
+    fn sql_type(&self) -> SnowflakeType {
+        SnowflakeType::Binary
+    }
+
+    fn null_sql_type() -> SnowflakeType {
+        SnowflakeType::Binary
+    }
+
+    fn to_sql(&self, out: &mut BytesMut) -> Result<IsNull, BindingError> {
+        match self.format {
+            BinaryFormat::Hex => self.data.as_slice().to_sql(out),
+            BinaryFormat::Base64 => {
+                out.extend_from_slice(BASE64_STANDARD.encode(&self.data).as_bytes());
+                Ok(IsNull::No)
+            }
+        }
+    }
+
+    fn encode_format(&self) -> &'static str {
+        self.format.as_str()
+    }
+}
+
+/// Durations are bound as a `FIXED` number of seconds, the same way as with the `chrono` feature.
+/// Sub-second part is kept as a decimal fraction with up to nanosecond precision.
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// use snowflake_api::bindings::ToSql;
+/// use snowflake_api::{BindingValue, ParameterBinding, SnowflakeType};
+///
+/// let binding = ParameterBinding::try_from(&Duration::from_millis(90_250) as &dyn ToSql).unwrap();
+/// assert_eq!(binding.type_, Some(SnowflakeType::Fixed));
+/// assert_eq!(binding.value, Some(BindingValue::SingleBind("90.25".to_owned())));
+/// ```
+impl ToSql for std::time::Duration {
+    fn sql_type(&self) -> SnowflakeType {
+        SnowflakeType::Fixed
+    }
+
+    fn null_sql_type() -> SnowflakeType {
+        SnowflakeType::Fixed
+    }
+
+    fn to_sql(&self, out: &mut BytesMut) -> Result<IsNull, BindingError> {
+        write_seconds(out, false, self.as_secs(), self.subsec_nanos())?;
+        Ok(IsNull::No)
+    }
+
+    default_encode!();
+}
+
+/// System time is bound as a UTC `TIMESTAMP_TZ` with nanosecond precision,
+/// times before the UNIX epoch included.
+///
+/// ```rust
+/// use std::time::{Duration, SystemTime, UNIX_EPOCH};
+///
+/// use snowflake_api::bindings::ToSql;
+/// use snowflake_api::{BindingValue, ParameterBinding, SnowflakeType};
+///
+/// let time = UNIX_EPOCH + Duration::new(1_706_702_415, 123_456_789);
+/// let binding = ParameterBinding::try_from(&time as &dyn ToSql).unwrap();
+/// assert_eq!(binding.type_, Some(SnowflakeType::TimestampTz));
+/// assert_eq!(
+///     binding.value,
+///     Some(BindingValue::SingleBind("2024-01-31 12:00:15.123456789 +00:00".to_owned()))
+/// );
+///
+/// let time = UNIX_EPOCH - Duration::from_millis(1500);
+/// let binding = ParameterBinding::try_from(&time as &dyn ToSql).unwrap();
+/// assert_eq!(
+///     binding.value,
+///     Some(BindingValue::SingleBind("1969-12-31 23:59:58.500000000 +00:00".to_owned()))
+/// );
+/// ```
+impl ToSql for std::time::SystemTime {
+    fn sql_type(&self) -> SnowflakeType {
+        SnowflakeType::TimestampTz
+    }
+
+    fn null_sql_type() -> SnowflakeType {
+        SnowflakeType::TimestampTz
+    }
+
+    fn to_sql(&self, out: &mut BytesMut) -> Result<IsNull, BindingError> {
+        let nanos = match self.duration_since(std::time::UNIX_EPOCH) {
+            Ok(since) => i128::try_from(since.as_nanos()),
+            Err(e) => i128::try_from(e.duration().as_nanos()).map(|n| -n),
+        }
+        .map_err(|_| BindingError::TimestampOutOfRange)?;
+        let secs = i64::try_from(nanos.div_euclid(1_000_000_000))
+            .map_err(|_| BindingError::TimestampOutOfRange)?;
+        let (days, secs_of_day) = (secs.div_euclid(86_400), secs.rem_euclid(86_400));
+        let (year, month, day) = civil_from_days(days);
+
+        // matches the `chrono::DateTime<Utc>` format
+        write!(
+            out,
+            "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}.{:09} +00:00",
+            secs_of_day / 3600,
+            secs_of_day / 60 % 60,
+            secs_of_day % 60,
+            nanos.rem_euclid(1_000_000_000),
+        )?;
+        Ok(IsNull::No)
+    }
+
+    default_encode!();
+}
+
+/// Proleptic Gregorian date of the given number of days since the UNIX epoch,
+/// see <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    // months counted from March, so that the leap day is the last day of the year
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Writes seconds with the sub-second part as a decimal fraction without trailing zeros
+fn write_seconds(
+    out: &mut BytesMut,
+    negative: bool,
+    secs: u64,
+    nanos: u32,
+) -> Result<(), BindingError> {
+    if negative {
+        out.write_char('-')?;
+    }
+    write!(out, "{secs}")?;
+    if nanos != 0 {
+        let fraction = format!("{nanos:09}");
+        write!(out, ".{}", fraction.trim_end_matches('0'))?;
+    }
+    Ok(())
+}
+
+/// `None` is bound as NULL typed with [`ToSql::null_sql_type`] of the inner type.
+///
+/// For arrays this makes a NULL array distinct from an empty one: `None` binds no value at all,
+/// while `Some` of an empty array binds an empty [`BindingValue::MultiBind`].
+/// Nested options don't wrap again, `Some(None)` binds the same NULL as `None`.
+///
+/// ```rust
+/// use snowflake_api::bindings::{Array, ToSql};
+/// use snowflake_api::{BindingValue, ParameterBinding, SnowflakeType};
+///
+/// let null_array: Option<Array<i64>> = None;
+/// let binding = ParameterBinding::try_from(&null_array as &dyn ToSql).unwrap();
+/// assert_eq!(binding.type_, Some(SnowflakeType::Fixed));
+/// assert_eq!(binding.value, None);
+///
+/// let empty = Some(Array::<i64>::new(vec![]));
+/// let binding = ParameterBinding::try_from(&empty as &dyn ToSql).unwrap();
+/// assert_eq!(binding.type_, Some(SnowflakeType::Fixed));
+/// assert_eq!(binding.value, Some(BindingValue::MultiBind(vec![])));
+///
+/// let values = Some(Array::new(vec![Some(1), None]));
+/// let binding = ParameterBinding::try_from(&values as &dyn ToSql).unwrap();
+/// assert_eq!(binding.type_, Some(SnowflakeType::Fixed));
+/// assert_eq!(
+///     binding.value,
+///     Some(BindingValue::MultiBind(vec![Some("1".to_owned()), None]))
+/// );
+///
+/// let nested: Option<Option<i64>> = Some(None);
+/// let binding = ParameterBinding::try_from(&nested as &dyn ToSql).unwrap();
+/// assert_eq!(binding.type_, Some(SnowflakeType::Fixed));
+/// assert_eq!(binding.value, None);
+///
+/// let nested = Some(Some(7_i64));
+/// let binding = ParameterBinding::try_from(&nested as &dyn ToSql).unwrap();
+/// assert_eq!(binding.value, Some(BindingValue::SingleBind("7".to_owned())));
+/// ```
+impl<T: ToSql> ToSql for Option<T> {
+    fn sql_type(&self) -> SnowflakeType {
+        match self {
+            Some(v) => v.sql_type(),
+            None => T::null_sql_type(),
+        }
+    }
+
+    fn null_sql_type() -> SnowflakeType {
+        T::null_sql_type()
+    }
+
+    fn to_sql(&self, out: &mut BytesMut) -> Result<IsNull, BindingError> {
+        match self {
+            Some(v) => v.to_sql(out),
+            None => Ok(IsNull::Yes),
+        }
+    }
+
+    fn to_sql_multi(&self) -> Result<Option<Vec<Option<String>>>, BindingError> {
+        match self {
+            Some(v) => v.to_sql_multi(),
+            None => Ok(None),
+        }
+    }
+
+    fn encode_format(&self) -> &'static str {
+        match self {
+            Some(v) => v.encode_format(),
+            None => "",
+        }
+    }
+}
+
+impl<T: ToSql> ToSql for &T {
+    fn sql_type(&self) -> SnowflakeType {
+        (*self).sql_type()
+    }
+
+    fn null_sql_type() -> SnowflakeType {
+        T::null_sql_type()
+    }
+
+    fn to_sql(&self, out: &mut BytesMut) -> Result<IsNull, BindingError> {
+        (*self).to_sql(out)
+    }
+
+    fn to_sql_multi(&self) -> Result<Option<Vec<Option<String>>>, BindingError> {
+        (*self).to_sql_multi()
+    }
+
+    fn encode_format(&self) -> &'static str {
+        (*self).encode_format()
+    }
+}
+
+/// Same as the shared reference, eg for values held mutably while building a request
+///
+/// ```rust
+/// use snowflake_api::{BindingValue, ExecRequestBuilder, SnowflakeType};
+///
+/// let mut name = String::from("Ferris");
+/// let name_ref = &mut name;
+/// name_ref.push_str(" the crab");
+/// let request = ExecRequestBuilder::new()
+///     .sql("INSERT INTO USERS (NAME) VALUES (?)")
+///     .bind("1", name_ref)
+///     .unwrap();
+/// let bindings = request.bindings();
+/// assert_eq!(bindings["1"].type_, Some(SnowflakeType::Text));
+/// assert_eq!(
+///     bindings["1"].value,
+///     Some(BindingValue::SingleBind("Ferris the crab".to_owned()))
+/// );
+/// ```
+impl<T: ToSql> ToSql for &mut T {
+    fn sql_type(&self) -> SnowflakeType {
+        (**self).sql_type()
+    }
+
+    fn null_sql_type() -> SnowflakeType {
+        T::null_sql_type()
+    }
+
+    fn to_sql(&self, out: &mut BytesMut) -> Result<IsNull, BindingError> {
+        (**self).to_sql(out)
+    }
+
+    fn to_sql_multi(&self) -> Result<Option<Vec<Option<String>>>, BindingError> {
+        (**self).to_sql_multi()
+    }
+
+    fn encode_format(&self) -> &'static str {
+        (**self).encode_format()
+    }
+}
+
+impl<T: ToSql> ToSql for Box<T> {
+    fn sql_type(&self) -> SnowflakeType {
+        self.as_ref().sql_type()
+    }
+
+    fn null_sql_type() -> SnowflakeType {
+        T::null_sql_type()
+    }
+
+    fn to_sql(&self, out: &mut BytesMut) -> Result<IsNull, BindingError> {
+        self.as_ref().to_sql(out)
+    }
+
+    fn to_sql_multi(&self) -> Result<Option<Vec<Option<String>>>, BindingError> {
+        self.as_ref().to_sql_multi()
+    }
+
+    fn encode_format(&self) -> &'static str {
+        self.as_ref().encode_format()
+    }
+}
+
+impl ToSql for Box<dyn ToSql> {
+    fn sql_type(&self) -> SnowflakeType {
+        self.as_ref().sql_type()
+    }
+
+    fn to_sql(&self, out: &mut BytesMut) -> Result<IsNull, BindingError> {
+        self.as_ref().to_sql(out)
+    }
+
+    fn to_sql_multi(&self) -> Result<Option<Vec<Option<String>>>, BindingError> {
+        self.as_ref().to_sql_multi()
+    }
+
+    fn encode_format(&self) -> &'static str {
+        self.as_ref().encode_format()
+    }
+}
+
+/// Same as the boxed trait object, for values of different types bound without boxing them
+///
+/// ```rust
+/// use snowflake_api::bindings::ToSql;
+/// use snowflake_api::{BindingValue, ExecRequestBuilder, ParameterBinding, SnowflakeType};
+///
+/// let name = String::from("Ferris");
+/// let values: [&dyn ToSql; 2] = [&name, &42i64];
+/// let mut request = ExecRequestBuilder::new().sql("INSERT INTO USERS (NAME, AGE) VALUES (?, ?)");
+/// for (name, value) in ["1", "2"].into_iter().zip(values) {
+///     request = request.bind(name, value).unwrap();
+/// }
+/// let bindings = request.bindings();
+/// assert_eq!(bindings["1"].type_, Some(SnowflakeType::Text));
+/// assert_eq!(bindings["1"].value, Some(BindingValue::SingleBind("Ferris".to_owned())));
+/// assert_eq!(bindings["2"].type_, Some(SnowflakeType::Fixed));
+///
+/// let boxed: Box<dyn ToSql> = Box::new(42i64);
+/// assert_eq!(bindings["2"], ParameterBinding::try_from(boxed).unwrap());
+/// ```
+impl ToSql for &dyn ToSql {
+    fn sql_type(&self) -> SnowflakeType {
+        (**self).sql_type()
+    }
+
+    fn to_sql(&self, out: &mut BytesMut) -> Result<IsNull, BindingError> {
+        (**self).to_sql(out)
+    }
+
+    fn to_sql_multi(&self) -> Result<Option<Vec<Option<String>>>, BindingError> {
+        (**self).to_sql_multi()
+    }
+
+    fn encode_format(&self) -> &'static str {
+        (**self).encode_format()
+    }
+}
+
+macro_rules! shared_impl {
+    ($(#[$attr:meta])* $t:ident) => {
+        $(#[$attr])*
+        impl<T: ToSql> ToSql for $t<T> {
+            fn sql_type(&self) -> SnowflakeType {
+                self.as_ref().sql_type()
+            }
+
+            fn null_sql_type() -> SnowflakeType {
+                T::null_sql_type()
+            }
+
+            fn to_sql(&self, out: &mut BytesMut) -> Result<IsNull, BindingError> {
+                self.as_ref().to_sql(out)
+            }
+
+            fn to_sql_multi(&self) -> Result<Option<Vec<Option<String>>>, BindingError> {
+                self.as_ref().to_sql_multi()
+            }
+
+            fn encode_format(&self) -> &'static str {
+                self.as_ref().encode_format()
+            }
+        }
+    };
+}
+
+shared_impl!(
+    /// Shared values bind the same way as the inner value
+    ///
+    /// ```rust
+    /// use std::sync::Arc;
+    ///
+    /// use snowflake_api::bindings::ToSql;
+    /// use snowflake_api::{BindingValue, ParameterBinding, SnowflakeType};
+    ///
+    /// let value = Arc::new("shared".to_string());
+    /// let binding = ParameterBinding::try_from(&value as &dyn ToSql).unwrap();
+    /// assert_eq!(binding.type_, Some(SnowflakeType::Text));
+    /// assert_eq!(binding.value, Some(BindingValue::SingleBind("shared".to_owned())));
+    /// ```
+    Arc
+);
+shared_impl!(Rc);
+
+/// NULL of the given type, for cases where there is no value to make a typed `None` from, see [`null_of`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TypedNull(pub SnowflakeType);
+
+impl ToSql for TypedNull {
+    fn sql_type(&self) -> SnowflakeType {
+        self.0
+    }
+
+    fn to_sql(&self, _out: &mut BytesMut) -> Result<IsNull, BindingError> {
+        Ok(IsNull::Yes)
+    }
+
+    default_encode!();
+}
+
+/// NULL bound as the given type, same as `None::<T>` of a type binding as it
+///
+/// ```rust
+/// use snowflake_api::bindings::null_of;
+/// use snowflake_api::{ExecRequestBuilder, ParameterBinding, SnowflakeType};
+///
+/// let binding = ParameterBinding::from_value(null_of(SnowflakeType::Fixed)).unwrap();
+/// assert_eq!(binding.type_, Some(SnowflakeType::Fixed));
+/// assert_eq!(binding.value, None);
+/// assert_eq!(binding, ParameterBinding::from_value(None::<i64>).unwrap());
+///
+/// let request = ExecRequestBuilder::new()
+///     .sql("UPDATE ORDERS SET SHIPPED_AT = ? WHERE ID = ?")
+///     .bind("1", null_of(SnowflakeType::TimestampNtz))
+///     .unwrap();
+/// assert_eq!(request.bindings()["1"].type_, Some(SnowflakeType::TimestampNtz));
+/// ```
+pub fn null_of(type_: SnowflakeType) -> TypedNull {
+    TypedNull(type_)
+}
+
+/// Binds inner value as `VARIANT`, erasing its type, see [`TypedVariant`] to avoid boxing
+pub struct Variant(Box<dyn ToSql>);
+
+impl Variant {
+    pub fn new<T: ToSql + 'static>(value: T) -> Self {
+        Self(Box::new(value))
+    }
+}
+
+impl From<Box<dyn ToSql>> for Variant {
+    fn from(value: Box<dyn ToSql>) -> Self {
+        Self(value)
+    }
+}
+
+impl ToSql for Variant {
+    fn sql_type(&self) -> SnowflakeType {
+        SnowflakeType::Variant
+    }
+
+    fn null_sql_type() -> SnowflakeType {
+        SnowflakeType::Variant
+    }
+
+    fn to_sql(&self, out: &mut BytesMut) -> Result<IsNull, BindingError> {
+        self.0.to_sql(out)
+    }
+
+    fn encode_format(&self) -> &'static str {
+        self.0.encode_format()
+    }
+}
+
+/// Binds inner value as `VARIANT` the same way as [`Variant`], without boxing it
+///
+/// ```rust
+/// use snowflake_api::bindings::{ToSql, TypedVariant, Variant};
+/// use snowflake_api::{ParameterBinding, SnowflakeType};
+///
+/// let typed = ParameterBinding::try_from(&TypedVariant(42u32) as &dyn ToSql).unwrap();
+/// let boxed = ParameterBinding::try_from(&Variant::new(42u32) as &dyn ToSql).unwrap();
+/// assert_eq!(typed.type_, Some(SnowflakeType::Variant));
+/// assert_eq!(typed, boxed);
+///
+/// let null = ParameterBinding::try_from(&None::<TypedVariant<u32>> as &dyn ToSql).unwrap();
+/// assert_eq!(null.type_, Some(SnowflakeType::Variant));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TypedVariant<T: ToSql>(pub T);
+
+impl<T: ToSql> ToSql for TypedVariant<T> {
+    fn sql_type(&self) -> SnowflakeType {
+        SnowflakeType::Variant
+    }
+
+    fn null_sql_type() -> SnowflakeType {
+        SnowflakeType::Variant
+    }
+
+    fn to_sql(&self, out: &mut BytesMut) -> Result<IsNull, BindingError> {
+        self.0.to_sql(out)
+    }
+
+    fn encode_format(&self) -> &'static str {
+        self.0.encode_format()
+    }
+}
+
+impl<T: ToSql + 'static> From<TypedVariant<T>> for Variant {
+    fn from(value: TypedVariant<T>) -> Self {
+        Self::new(value.0)
+    }
+}
+
+macro_rules! geospatial_impl {
+    ($(#[$attr:meta])* $name:ident, $sql_type:expr) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub struct $name(String);
+
+        impl $name {
+            /// Wraps a WKT string, eg `POINT(-122.35 37.55)`, empty strings are rejected.
+            /// Shapes of the `geo` crate can be wrapped with `from_geo` with the `geo` feature.
+            pub fn new(wkt: impl Into<String>) -> Result<Self, BindingError> {
+                let wkt = wkt.into();
+                if wkt.trim().is_empty() {
+                    return Err(BindingError::EmptyWkt);
+                }
+                Ok(Self(wkt))
+            }
+
+            pub fn wkt(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl ToSql for $name {
+            fn sql_type(&self) -> SnowflakeType {
+                $sql_type
+            }
+
+            fn null_sql_type() -> SnowflakeType {
+                $sql_type
+            }
+
+            fn to_sql(&self, out: &mut BytesMut) -> Result<IsNull, BindingError> {
+                out.write_str(&self.0)?;
+                Ok(IsNull::No)
+            }
+
+            // WKT is detected by the server, same as WKB and GeoJSON
+            default_encode!();
+        }
+    };
+}
+
+geospatial_impl!(
+    /// Binds WKT text as `GEOGRAPHY`, coordinates are longitude and latitude in degrees
+    ///
+    /// ```rust
+    /// use snowflake_api::bindings::{BindingError, Geography, ToSql};
+    /// use snowflake_api::{BindingValue, ParameterBinding, SnowflakeType};
+    ///
+    /// let point = Geography::new("POINT(-122.35 37.55)").unwrap();
+    /// let binding = ParameterBinding::try_from(&point as &dyn ToSql).unwrap();
+    /// assert_eq!(binding.type_, Some(SnowflakeType::Geography));
+    /// assert_eq!(
+    ///     binding.value,
+    ///     Some(BindingValue::SingleBind("POINT(-122.35 37.55)".to_owned()))
+    /// );
+    ///
+    /// assert!(matches!(Geography::new(" "), Err(BindingError::EmptyWkt)));
+    /// ```
+    Geography,
+    SnowflakeType::Geography
+);
+
+geospatial_impl!(
+    /// Binds WKT text as `GEOMETRY`, coordinates are in the planar system of the column's SRID
+    ///
+    /// ```rust
+    /// use snowflake_api::bindings::{Geometry, ToSql};
+    /// use snowflake_api::{BindingValue, ParameterBinding, SnowflakeType};
+    ///
+    /// let wkt = "POLYGON((0 0, 10 0, 10 10, 0 10, 0 0))";
+    /// let polygon = Geometry::new(wkt).unwrap();
+    /// let binding = ParameterBinding::try_from(&polygon as &dyn ToSql).unwrap();
+    /// assert_eq!(binding.type_, Some(SnowflakeType::Geometry));
+    /// assert_eq!(binding.value, Some(BindingValue::SingleBind(wkt.to_owned())));
+    /// ```
+    Geometry,
+    SnowflakeType::Geometry
+);
+
+/// Binds an interval as `TEXT`, in the form of the string of an interval constant, eg `1 day, 3 hours`.
+///
+/// Snowflake has no interval type for bindings, so the value can't be used in date arithmetic
+/// as a parameter. Use [`Interval::to_literal`] to add it to the SQL text, or bind it for
+/// code parsing the text, eg a stored procedure building the constant.
+///
+/// ```rust
+/// use snowflake_api::bindings::{Interval, ToSql};
+/// use snowflake_api::{BindingValue, ParameterBinding, SnowflakeType};
+///
+/// let binding = ParameterBinding::from_value(Interval::minutes(90)).unwrap();
+/// assert_eq!(binding.type_, Some(SnowflakeType::Text));
+/// assert_eq!(binding.value, Some(BindingValue::SingleBind("90 minutes".to_owned())));
+///
+/// let interval = Interval::days(1) + Interval::hours(3);
+/// assert_eq!(interval.to_string(), "1 day, 3 hours");
+/// assert_eq!(interval.to_literal(), "INTERVAL '1 day, 3 hours'");
+///
+/// let interval = Interval::weeks(2) + Interval::days(1) + Interval::milliseconds(250);
+/// assert_eq!(interval.to_string(), "2 weeks, 1 day, 250 milliseconds");
+///
+/// let sql = format!("SELECT CURRENT_TIMESTAMP() - {}", Interval::days(2).to_literal());
+/// assert_eq!(sql, "SELECT CURRENT_TIMESTAMP() - INTERVAL '2 days'");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interval(Vec<(u64, &'static str)>);
+
+macro_rules! interval_parts {
+    ($($name:ident $part:literal),+) => {
+        impl Interval {
+            $(
+                #[doc = concat!("Interval of the given number of ", $part, "s")]
+                #[must_use]
+                pub fn $name(n: u64) -> Self {
+                    Self(vec![(n, $part)])
+                }
+            )+
+        }
+    };
+}
+
+interval_parts!(
+    years "year",
+    quarters "quarter",
+    months "month",
+    weeks "week",
+    days "day",
+    hours "hour",
+    minutes "minute",
+    seconds "second",
+    milliseconds "millisecond",
+    microseconds "microsecond",
+    nanoseconds "nanosecond"
+);
+
+impl Interval {
+    /// Interval constant for the SQL text, eg `INTERVAL '1 day, 3 hours'`
+    pub fn to_literal(&self) -> String {
+        format!("INTERVAL '{self}'")
+    }
+}
+
+/// Parts are kept in order, the same part added twice is summed by Snowflake
+impl std::ops::Add for Interval {
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self {
+        self.0.extend(other.0);
+        self
+    }
+}
+
+impl std::fmt::Display for Interval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, (n, part)) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{n} {part}{}", if *n == 1 { "" } else { "s" })?;
+        }
+        Ok(())
+    }
+}
+
+impl ToSql for Interval {
+    fn sql_type(&self) -> SnowflakeType {
+        SnowflakeType::Text
+    }
+
+    fn null_sql_type() -> SnowflakeType {
+        SnowflakeType::Text
+    }
+
+    fn to_sql(&self, out: &mut BytesMut) -> Result<IsNull, BindingError> {
+        write!(out, "{self}")?;
+        Ok(IsNull::No)
+    }
+
+    default_encode!();
+}
+
+/// Binds serializable value as JSON `OBJECT`, see [`VariantObject`] for `VARIANT` columns
+pub struct Object<T>(T);
+
+impl<T: Serialize> Object<T> {
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T: Serialize> From<T> for Object<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T: Serialize> ToSql for Object<T> {
+    fn sql_type(&self) -> SnowflakeType {
+        SnowflakeType::Object
+    }
+
+    fn null_sql_type() -> SnowflakeType {
+        SnowflakeType::Object
+    }
+
+    fn to_sql(&self, out: &mut BytesMut) -> Result<IsNull, BindingError> {
+        serde_json::to_writer(out.writer(), &self.0)?;
+        Ok(IsNull::No)
+    }
+
+    fn encode_format(&self) -> &'static str {
+        "json"
+    }
+}
+
+/// Binds serializable value as JSON `VARIANT`, same as [`Object`] apart from the type.
+///
+/// Use [`Object`] for `OBJECT` columns, which only hold key-value maps, and `VariantObject`
+/// for `VARIANT` columns, eg when the column also stores arrays or scalars,
+/// or when the value is passed to functions expecting a `VARIANT`.
+///
+/// ```rust
+/// use serde::Serialize;
+/// use snowflake_api::bindings::{ToSql, VariantObject};
+/// use snowflake_api::{BindingValue, ParameterBinding, SnowflakeType};
+///
+/// #[derive(Serialize)]
+/// struct Address {
+///     city: String,
+///     zip: Option<String>,
+/// }
+///
+/// #[derive(Serialize)]
+/// struct Customer {
+///     name: String,
+///     tags: Vec<&'static str>,
+///     address: Address,
+/// }
+///
+/// let customer = Customer {
+///     name: "Ferris".to_owned(),
+///     tags: vec!["crab", "rust"],
+///     address: Address {
+///         city: "Berlin".to_owned(),
+///         zip: None,
+///     },
+/// };
+/// let binding = ParameterBinding::try_from(&VariantObject::new(customer) as &dyn ToSql).unwrap();
+/// assert_eq!(binding.type_, Some(SnowflakeType::Variant));
+/// assert_eq!(binding.fmt.as_deref(), Some("json"));
+/// assert_eq!(
+///     binding.value,
+///     Some(BindingValue::SingleBind(
+///         r#"{"name":"Ferris","tags":["crab","rust"],"address":{"city":"Berlin","zip":null}}"#
+///             .to_owned()
+///     ))
+/// );
+/// ```
+pub struct VariantObject<T>(T);
+
+impl<T: Serialize> VariantObject<T> {
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T: Serialize> From<T> for VariantObject<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T: Serialize> ToSql for VariantObject<T> {
+    fn sql_type(&self) -> SnowflakeType {
+        SnowflakeType::Variant
+    }
+
+    fn null_sql_type() -> SnowflakeType {
+        SnowflakeType::Variant
+    }
+
+    fn to_sql(&self, out: &mut BytesMut) -> Result<IsNull, BindingError> {
+        serde_json::to_writer(out.writer(), &self.0)?;
+        Ok(IsNull::No)
+    }
+
+    fn encode_format(&self) -> &'static str {
+        "json"
+    }
+}
+
+impl<V: Serialize> ToSql for BTreeMap<String, V> {
+    fn sql_type(&self) -> SnowflakeType {
+        SnowflakeType::Object
+    }
+
+    fn null_sql_type() -> SnowflakeType {
+        SnowflakeType::Object
+    }
+
+    fn to_sql(&self, out: &mut BytesMut) -> Result<IsNull, BindingError> {
+        serde_json::to_writer(out.writer(), self)?;
+        Ok(IsNull::No)
+    }
+
+    fn encode_format(&self) -> &'static str {
+        "json"
+    }
+}
+
+impl<V: Serialize, S: BuildHasher> ToSql for HashMap<String, V, S> {
+    fn sql_type(&self) -> SnowflakeType {
+        SnowflakeType::Object
+    }
+
+    fn null_sql_type() -> SnowflakeType {
+        SnowflakeType::Object
+    }
+
+    fn to_sql(&self, out: &mut BytesMut) -> Result<IsNull, BindingError> {
+        serde_json::to_writer(out.writer(), self)?;
+        Ok(IsNull::No)
+    }
+
+    fn encode_format(&self) -> &'static str {
+        "json"
+    }
+}
+
+/// `Value::Null` is bound as JSON `null` inside the `VARIANT`, which is distinct from SQL NULL
+///
+/// ```rust
+/// use serde_json::{json, Value};
+/// use snowflake_api::{BindingValue, ParameterBinding, SnowflakeType};
+///
+/// let text = |s: &str| Some(BindingValue::SingleBind(s.to_owned()));
+///
+/// let binding = ParameterBinding::from_value(Value::Null).unwrap();
+/// assert_eq!(binding.type_, Some(SnowflakeType::Variant));
+/// assert_eq!(binding.value, text("null"));
+/// assert_eq!(binding.fmt.as_deref(), Some("json"));
+///
+/// let object = json!({ "id": 1, "tags": ["a", "b"], "parent": null });
+/// let binding = ParameterBinding::from_value(object).unwrap();
+/// assert_eq!(binding.value, text(r#"{"id":1,"parent":null,"tags":["a","b"]}"#));
+///
+/// let array = json!([1, null, { "nested": [null] }]);
+/// let binding = ParameterBinding::from_value(array).unwrap();
+/// assert_eq!(binding.value, text(r#"[1,null,{"nested":[null]}]"#));
+/// ```
+impl ToSql for serde_json::Value {
+    fn sql_type(&self) -> SnowflakeType {
+        SnowflakeType::Variant
+    }
+
+    fn null_sql_type() -> SnowflakeType {
+        SnowflakeType::Variant
+    }
+
+    fn to_sql(&self, out: &mut BytesMut) -> Result<IsNull, BindingError> {
+        serde_json::to_writer(out.writer(), self)?;
+        Ok(IsNull::No)
+    }
+
+    fn encode_format(&self) -> &'static str {
+        "json"
+    }
+}
+
+/// Binds elements as an array, eg to insert multiple rows with a single `INSERT ... VALUES (?)`.
+///
+/// Snowflake expects one type for the whole array, which is taken from the first non-null
+/// element. Empty and all-null arrays use the NULL type of the element, see [`ToSql::null_sql_type`].
+///
+/// ```rust
+/// use snowflake_api::bindings::Array;
+/// use snowflake_api::{BindingValue, ParameterBinding, SnowflakeType};
+///
+/// let binding = ParameterBinding::try_from(Array::new(vec![None, Some(1), Some(2)])).unwrap();
+/// assert_eq!(binding.type_, Some(SnowflakeType::Fixed));
+/// assert_eq!(
+///     binding.value,
+///     Some(BindingValue::MultiBind(vec![None, Some("1".to_string()), Some("2".to_string())]))
+/// );
+/// ```
+pub struct Array<T>(Vec<T>);
+
+impl<T: ToSql> Array<T> {
+    pub fn new(values: Vec<T>) -> Self {
+        Self(values)
+    }
+}
+
+impl<T: ToSql> From<Vec<T>> for Array<T> {
+    fn from(values: Vec<T>) -> Self {
+        Self(values)
+    }
+}
+
+/// Slices bind as arrays through a copy, `&[T]` itself is not [`ToSql`]
+/// as `&[u8]` is already bound as `BINARY`
+impl<T: ToSql + Clone> From<&[T]> for Array<T> {
+    fn from(values: &[T]) -> Self {
+        Self(values.to_vec())
+    }
+}
+
+impl<T: ToSql> ToSql for Array<T> {
+    fn sql_type(&self) -> SnowflakeType {
+        elements_sql_type(&self.0)
+    }
+
+    fn null_sql_type() -> SnowflakeType {
+        T::null_sql_type()
+    }
+
+    /// When bound as a single value, eg inside a [`Variant`], elements are written
+    /// as a JSON array of their text representations
+    fn to_sql(&self, out: &mut BytesMut) -> Result<IsNull, BindingError> {
+        elements_to_json(&self.0, out)
+    }
+
+    fn to_sql_multi(&self) -> Result<Option<Vec<Option<String>>>, BindingError> {
+        elements_to_text(&self.0).map(Some)
+    }
+
+    fn encode_format(&self) -> &'static str {
+        elements_encode_format(&self.0)
+    }
+}
+
+/// Fixed-size arrays bind the same way as [`Array`], preserving element order.
+/// Zero-length arrays bind no values, typed with the element NULL type.
+///
+/// ```rust
+/// use snowflake_api::bindings::ToSql;
+/// use snowflake_api::{BindingValue, ParameterBinding, SnowflakeType};
+///
+/// let binding = ParameterBinding::try_from(&[1, 2, 3] as &dyn ToSql).unwrap();
+/// assert_eq!(binding.type_, Some(SnowflakeType::Fixed));
+/// assert_eq!(
+///     binding.value,
+///     Some(BindingValue::MultiBind(vec![
+///         Some("1".to_string()),
+///         Some("2".to_string()),
+///         Some("3".to_string())
+///     ]))
+/// );
+///
+/// let binding = ParameterBinding::try_from(&[] as &[i32; 0] as &dyn ToSql).unwrap();
+/// assert_eq!(binding.type_, Some(SnowflakeType::Fixed));
+/// assert_eq!(binding.value, Some(BindingValue::MultiBind(vec![])));
+/// ```
+impl<T: ToSql, const N: usize> ToSql for [T; N] {
+    fn sql_type(&self) -> SnowflakeType {
+        elements_sql_type(self)
+    }
+
+    fn null_sql_type() -> SnowflakeType {
+        T::null_sql_type()
+    }
+
+    fn to_sql(&self, out: &mut BytesMut) -> Result<IsNull, BindingError> {
+        elements_to_json(self, out)
+    }
+
+    fn to_sql_multi(&self) -> Result<Option<Vec<Option<String>>>, BindingError> {
+        elements_to_text(self).map(Some)
+    }
+
+    fn encode_format(&self) -> &'static str {
+        elements_encode_format(self)
+    }
+}
+
+fn elements_sql_type<T: ToSql>(values: &[T]) -> SnowflakeType {
+    values
+        .iter()
+        .map(ToSql::sql_type)
+        .find(|t| *t != SnowflakeType::UnknownNull)
+        .unwrap_or_else(T::null_sql_type)
+}
+
+fn elements_to_json<T: ToSql>(values: &[T], out: &mut BytesMut) -> Result<IsNull, BindingError> {
+    serde_json::to_writer(out.writer(), &elements_to_text(values)?)?;
+    Ok(IsNull::No)
+}
+
+fn elements_to_text<T: ToSql>(values: &[T]) -> Result<Vec<Option<String>>, BindingError> {
+    values.iter().map(|v| to_text(v)).collect()
+}
+
+fn elements_encode_format<T: ToSql>(values: &[T]) -> &'static str {
+    values
+        .iter()
+        .map(ToSql::encode_format)
+        .find(|f| !f.is_empty())
+        .unwrap_or_default()
+}
+
+impl<T: ToSql> TryFrom<Array<T>> for ParameterBinding {
+    type Error = BindingError;
+
+    fn try_from(value: Array<T>) -> Result<Self, Self::Error> {
+        Self::from_value(value)
+    }
+}
+
+impl ParameterBinding {
+    /// Binding of the value, without boxing it or going through `dyn ToSql`
+    ///
+    /// ```rust
+    /// use snowflake_api::bindings::ToSql;
+    /// use snowflake_api::{BindingValue, ParameterBinding, SnowflakeType};
+    ///
+    /// let unboxed = ParameterBinding::from_value(42i64).unwrap();
+    /// assert_eq!(unboxed.type_, Some(SnowflakeType::Fixed));
+    /// assert_eq!(unboxed.value, Some(BindingValue::SingleBind("42".to_owned())));
+    ///
+    /// let boxed: Box<dyn ToSql> = Box::new(42i64);
+    /// assert_eq!(unboxed, ParameterBinding::try_from(boxed).unwrap());
+    /// ```
+    pub fn from_value<T: ToSql>(value: T) -> Result<Self, BindingError> {
+        Self::from_ref(&value)
+    }
+
+    pub(crate) fn from_ref<T: ToSql + ?Sized>(value: &T) -> Result<Self, BindingError> {
+        let binding = match value.to_sql_multi()? {
+            Some(values) => Some(BindingValue::MultiBind(values)),
+            None => to_text(value)?.map(BindingValue::SingleBind),
+        };
+        let fmt = value.encode_format();
+
+        Ok(ParameterBinding {
+            type_: Some(value.sql_type()),
+            value: binding,
+            fmt: (!fmt.is_empty()).then(|| fmt.to_owned()),
+        })
+    }
+}
+
+impl TryFrom<&dyn ToSql> for ParameterBinding {
+    type Error = BindingError;
+
+    fn try_from(value: &dyn ToSql) -> Result<Self, Self::Error> {
+        Self::from_ref(value)
+    }
+}
+
+impl TryFrom<Box<dyn ToSql>> for ParameterBinding {
+    type Error = BindingError;
+
+    fn try_from(value: Box<dyn ToSql>) -> Result<Self, Self::Error> {
+        Self::from_ref(value.as_ref())
+    }
+}
+
+/// A set of parameters which can be bound to a query.
+///
+/// Can be derived for structs with the `derive` feature, see [`ToBindings`](derive@ToBindings).
+///
+/// Tuples bind their elements as positional parameters `"1"`, `"2"`, ..., up to 8 elements.
+///
+/// ```rust
+/// use snowflake_api::bindings::ToBindings;
+/// use snowflake_api::{BindingValue, SnowflakeType};
+///
+/// let bindings = ("apple", 42, true, None::<f64>).to_bindings().unwrap();
+/// let types: Vec<_> = bindings.iter().map(|(k, b)| (k.as_str(), b.type_)).collect();
+/// assert_eq!(
+///     types,
+///     [
+///         ("1", Some(SnowflakeType::Text)),
+///         ("2", Some(SnowflakeType::Fixed)),
+///         ("3", Some(SnowflakeType::Boolean)),
+///         ("4", Some(SnowflakeType::Real)),
+///     ]
+/// );
+/// assert_eq!(bindings["4"].value, None);
+///
+/// let bindings = ("apple",).to_bindings().unwrap();
+/// assert_eq!(bindings.len(), 1);
+/// assert_eq!(bindings["1"].value, Some(BindingValue::SingleBind("apple".to_owned())));
+///
+/// let bindings = (1, "two").to_bindings().unwrap();
+/// assert_eq!(bindings["2"].type_, Some(SnowflakeType::Text));
+///
+/// let bindings = (1, 2.5, "three").to_bindings().unwrap();
+/// assert_eq!(bindings["2"].value, Some(BindingValue::SingleBind("2.5".to_owned())));
+/// ```
+pub trait ToBindings {
+    fn to_bindings(&self) -> Result<Bindings, BindingError>;
+}
+
+macro_rules! tuple_impl {
+    ($($name:ident $index:tt),+) => {
+        impl<$($name: ToSql),+> ToBindings for ($($name,)+) {
+            fn to_bindings(&self) -> Result<Bindings, BindingError> {
+                let mut bindings = Bindings::new();
+                $(
+                    let key = (bindings.len() + 1).to_string();
+                    let binding = ParameterBinding::from_ref(&self.$index)
+                        .map_err(|e| e.for_parameter(key.as_str()))?;
+                    bindings.insert(key, binding);
+                )+
+                Ok(bindings)
+            }
+        }
+    };
+}
+
+tuple_impl!(A 0);
+tuple_impl!(A 0, B 1);
+tuple_impl!(A 0, B 1, C 2);
+tuple_impl!(A 0, B 1, C 2, D 3);
+tuple_impl!(A 0, B 1, C 2, D 3, E 4);
+tuple_impl!(A 0, B 1, C 2, D 3, E 4, F 5);
+tuple_impl!(A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+tuple_impl!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+
+/// Bounds of the range as is, the start to `"1"` and the end to `"2"`
+fn range_bindings<T: ToSql>(start: &T, end: &T) -> Result<Bindings, BindingError> {
+    (start, end).to_bindings()
+}
+
+/// Ranges bind their start and end as two positional parameters `"1"` and `"2"`.
+///
+/// Bounds are bound unchanged, the SQL decides about inclusivity: inclusive ranges match
+/// `BETWEEN ? AND ?`, which includes both bounds, while half-open ranges need `>= ? AND < ?`,
+/// as the end isn't decremented. For ranges following other parameters bind the bounds
+/// one by one with [`ExecRequestBuilder::bind`](crate::ExecRequestBuilder::bind).
+///
+/// ```rust
+/// use snowflake_api::bindings::ToBindings;
+/// use snowflake_api::{BindingValue, ExecRequestBuilder};
+///
+/// let text = |s: &str| Some(BindingValue::SingleBind(s.to_owned()));
+///
+/// let request = ExecRequestBuilder::new()
+///     .sql("SELECT * FROM ORDERS WHERE ID >= ? AND ID < ?")
+///     .bind_all(&(1..10))
+///     .unwrap();
+/// let bindings = request.bindings();
+/// assert_eq!(bindings.len(), 2);
+/// assert_eq!(bindings["1"].value, text("1"));
+/// assert_eq!(bindings["2"].value, text("10"));
+///
+/// let bindings = (1..=10).to_bindings().unwrap();
+/// assert_eq!(bindings.len(), 2);
+/// assert_eq!(bindings["1"].value, text("1"));
+/// assert_eq!(bindings["2"].value, text("10"));
+/// ```
+impl<T: ToSql> ToBindings for std::ops::Range<T> {
+    fn to_bindings(&self) -> Result<Bindings, BindingError> {
+        range_bindings(&self.start, &self.end)
+    }
+}
+
+impl<T: ToSql> ToBindings for std::ops::RangeInclusive<T> {
+    fn to_bindings(&self) -> Result<Bindings, BindingError> {
+        range_bindings(self.start(), self.end())
+    }
+}
+
+/// Text representation of the value, `None` for NULL
+pub(crate) fn to_text<T: ToSql + ?Sized>(value: &T) -> Result<Option<String>, BindingError> {
+    let mut out = BytesMut::new();
+    match value.to_sql(&mut out)? {
+        IsNull::Yes => Ok(None),
+        IsNull::No => Ok(Some(String::from_utf8(out.to_vec())?)),
+    }
+}
+
+/// Quote a name for use as an identifier in dynamic SQL, where it can't be bound as a parameter.
+///
+/// The name is wrapped in double quotes and inner double quotes are doubled, so it is taken
+/// literally: case is preserved and it can't break out of the identifier.
+/// Note that quoted identifiers are case-sensitive, `"orders"` doesn't refer to a table
+/// created as `CREATE TABLE orders`, which Snowflake stores as `ORDERS`.
+///
+/// ```rust
+/// use snowflake_api::quote_identifier;
+///
+/// assert_eq!(quote_identifier("ORDERS"), r#""ORDERS""#);
+/// assert_eq!(quote_identifier("MixedCase"), r#""MixedCase""#);
+/// assert_eq!(quote_identifier("order items"), r#""order items""#);
+/// assert_eq!(quote_identifier(r#"say "hi""#), r#""say ""hi""""#);
+/// assert_eq!(quote_identifier(r#"a"; DROP TABLE t; --"#), r#""a""; DROP TABLE t; --""#);
+/// assert_eq!(
+///     format!("SELECT * FROM {}", quote_identifier("order items")),
+///     r#"SELECT * FROM "order items""#
+/// );
+/// ```
+pub fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Quote a string literal for dynamic SQL, prefer binding values as parameters where possible.
+///
+/// Single quotes are doubled and backslashes escaped, as Snowflake reads escape sequences
+/// in single-quoted strings.
+///
+/// ```rust
+/// use snowflake_api::quote_literal;
+///
+/// assert_eq!(quote_literal("apple"), "'apple'");
+/// assert_eq!(quote_literal("it's"), "'it''s'");
+/// assert_eq!(quote_literal(r"C:\data"), r"'C:\\data'");
+/// assert_eq!(quote_literal(r"\'; DROP TABLE t; --"), r"'\\''; DROP TABLE t; --'");
+/// ```
+pub fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "''"))
+}
+
+/// Decimal bound with exactly the given scale, eg to match the scale of a `NUMBER(38, 6)` column.
+///
+/// Values with fewer fractional digits are padded with zeros. Values with more are only
+/// accepted when the dropped digits are zeros, otherwise binding fails with
+/// [`BindingError::ScaleTruncation`] rather than rounding silently, round the value first
+/// if it is expected, eg with `Decimal::round_dp`.
+#[cfg(any(feature = "rust_decimal", feature = "bigdecimal"))]
+pub struct Scaled<D>(pub D, pub u32);
+
+/// Write the plain decimal text with exactly `scale` fractional digits
+#[cfg(any(feature = "rust_decimal", feature = "bigdecimal"))]
+fn write_scaled(out: &mut BytesMut, plain: &str, scale: u32) -> Result<(), BindingError> {
+    let (int, frac) = plain.split_once('.').unwrap_or((plain, ""));
+    let digits = scale as usize;
+    if frac.len() > digits && frac[digits..].bytes().any(|b| b != b'0') {
+        return Err(BindingError::ScaleTruncation(plain.to_owned(), scale));
+    }
+
+    out.write_str(int)?;
+    if digits > 0 {
+        let kept = &frac[..frac.len().min(digits)];
+        write!(out, ".{kept:0<digits$}")?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "bigdecimal")]
+mod bigdecimal_impls {
+    use bytes::BytesMut;
+
+    use super::{to_text, write_scaled, BindingError, IsNull, Scaled, ToSql};
+    use crate::responses::SnowflakeType;
+
+    /// Maximum scale of Snowflake `NUMBER`
+    const MAX_SCALE: i64 = 37;
+
+    /// Decimals are always written in plain notation, as Snowflake's `NUMBER` parser
+    /// doesn't accept exponents. Precision isn't capped client-side, values which don't fit
+    /// into `NUMBER(38, s)` are rejected by the server.
+    ///
+    /// ```rust
+    /// use bigdecimal::BigDecimal;
+    /// use snowflake_api::{BindingValue, ParameterBinding, SnowflakeType};
+    ///
+    /// let bound = |value: &str| {
+    ///     let value: BigDecimal = value.parse().unwrap();
+    ///     ParameterBinding::from_value(value).unwrap().value
+    /// };
+    /// let text = |s: &str| Some(BindingValue::SingleBind(s.to_owned()));
+    ///
+    /// // beyond the range of i128
+    /// let digits = "-1234567890123456789012345678901234567890.123456789";
+    /// assert_eq!(bound(digits), text(digits));
+    ///
+    /// // negative scales are expanded rather than written with an exponent
+    /// assert_eq!(
+    ///     bound("1e50"),
+    ///     text("100000000000000000000000000000000000000000000000000")
+    /// );
+    /// assert_eq!(bound("-2.5E3"), text("-2500"));
+    ///
+    /// let binding = ParameterBinding::from_value(BigDecimal::from(1)).unwrap();
+    /// assert_eq!(binding.type_, Some(SnowflakeType::Fixed));
+    /// ```
+    impl ToSql for bigdecimal::BigDecimal {
+        fn sql_type(&self) -> SnowflakeType {
+            SnowflakeType::Fixed
+        }
+
+        fn null_sql_type() -> SnowflakeType {
+            SnowflakeType::Fixed
+        }
+
+        fn to_sql(&self, out: &mut BytesMut) -> Result<IsNull, BindingError> {
+            // trailing zeros beyond the maximum scale carry no value, drop them
+            // instead of writing out potentially huge strings
+            if self.fractional_digit_count() > MAX_SCALE {
+                self.normalized().write_plain_string(out)?;
+            } else {
+                self.write_plain_string(out)?;
+            }
+            Ok(IsNull::No)
+        }
+
+        default_encode!();
+    }
+
+    /// Same as `Scaled<rust_decimal::Decimal>`
+    ///
+    /// ```rust
+    /// use bigdecimal::BigDecimal;
+    /// use snowflake_api::bindings::Scaled;
+    /// use snowflake_api::{BindingValue, ParameterBinding};
+    ///
+    /// let value: BigDecimal = "-0.5".parse().unwrap();
+    /// let binding = ParameterBinding::from_value(Scaled(value, 3)).unwrap();
+    /// assert_eq!(binding.value, Some(BindingValue::SingleBind("-0.500".to_owned())));
+    /// ```
+    impl ToSql for Scaled<bigdecimal::BigDecimal> {
+        fn sql_type(&self) -> SnowflakeType {
+            SnowflakeType::Fixed
+        }
+
+        fn null_sql_type() -> SnowflakeType {
+            SnowflakeType::Fixed
+        }
+
+        fn to_sql(&self, out: &mut BytesMut) -> Result<IsNull, BindingError> {
+            let plain = to_text(&self.0)?.unwrap_or_default();
+            write_scaled(out, &plain, self.1)?;
+            Ok(IsNull::No)
+        }
+
+        default_encode!();
+    }
+}
+
+#[cfg(feature = "chrono")]
+mod chrono_impls {
+    use std::fmt::Write;
+
+    use bytes::BytesMut;
+    use chrono::Utc;
+
+    use super::{
+        elements_encode_format, elements_sql_type, elements_to_json, elements_to_text,
+        write_seconds, BindingError, IsNull, ToSql,
+    };
+    use crate::responses::SnowflakeType;
+
+    macro_rules! date_time_impl {
+        ($(#[$attr:meta])* $t:ty, $sql_type:expr, $format:expr) => {
+            $(#[$attr])*
+            impl ToSql for $t {
+                fn sql_type(&self) -> SnowflakeType {
+                    $sql_type
+                }
+
+                fn null_sql_type() -> SnowflakeType {
+                    $sql_type
+                }
+
+                fn to_sql(&self, out: &mut BytesMut) -> Result<IsNull, BindingError> {
+                    write!(out, "{}", self.format($format))?;
+                    Ok(IsNull::No)
+                }
+
+                default_encode!();
+            }
+        };
+    }
+
+    date_time_impl!(
+        /// Dates are bound as ISO `YYYY-MM-DD`, whatever the format of the session is.
+        /// The default `DATE_INPUT_FORMAT` of `AUTO` always accepts it. Sessions which set
+        /// another input format should override it for the statement, which keeps the format
+        /// of the rest of the session intact:
+        ///
+        /// ```rust
+        /// use chrono::NaiveDate;
+        /// use snowflake_api::{BindingValue, ExecRequestBuilder, SnowflakeType};
+        ///
+        /// let request = ExecRequestBuilder::new()
+        ///     .sql("INSERT INTO EVENTS (DAY) VALUES (?)")
+        ///     .bind("1", NaiveDate::from_ymd_opt(2024, 1, 31).unwrap())
+        ///     .unwrap()
+        ///     .parameter("DATE_INPUT_FORMAT", "YYYY-MM-DD");
+        ///
+        /// let binding = &request.bindings()["1"];
+        /// assert_eq!(binding.type_, Some(SnowflakeType::Date));
+        /// assert_eq!(binding.value, Some(BindingValue::SingleBind("2024-01-31".to_owned())));
+        /// assert_eq!(request.parameters()["DATE_INPUT_FORMAT"], "YYYY-MM-DD");
+        /// ```
+        chrono::NaiveDate,
+        SnowflakeType::Date,
+        "%Y-%m-%d"
+    );
+    // TIME supports up to nanosecond precision
+    date_time_impl!(
+        /// Times keep full nanosecond precision, which is the maximum of `TIME(9)`
+        ///
+        /// ```rust
+        /// use chrono::NaiveTime;
+        /// use snowflake_api::{BindingValue, ParameterBinding, SnowflakeType};
+        ///
+        /// let value = NaiveTime::from_hms_nano_opt(23, 59, 59, 123_456_789).unwrap();
+        /// let binding = ParameterBinding::from_value(value).unwrap();
+        /// assert_eq!(binding.type_, Some(SnowflakeType::Time));
+        /// assert_eq!(
+        ///     binding.value,
+        ///     Some(BindingValue::SingleBind("23:59:59.123456789".to_owned()))
+        /// );
+        /// ```
+        chrono::NaiveTime,
+        SnowflakeType::Time,
+        "%H:%M:%S%.9f"
+    );
+    date_time_impl!(
+        /// Timestamps keep full nanosecond precision, which is the maximum of `TIMESTAMP_NTZ(9)`
+        ///
+        /// ```rust
+        /// use chrono::NaiveDate;
+        /// use snowflake_api::bindings::ToSql;
+        /// use snowflake_api::{BindingValue, ParameterBinding};
+        ///
+        /// let value = NaiveDate::from_ymd_opt(2024, 1, 31)
+        ///     .unwrap()
+        ///     .and_hms_nano_opt(12, 30, 15, 123_456_789)
+        ///     .unwrap();
+        /// let binding = ParameterBinding::try_from(&value as &dyn ToSql).unwrap();
+        /// assert_eq!(
+        ///     binding.value,
+        ///     Some(BindingValue::SingleBind("2024-01-31 12:30:15.123456789".to_owned()))
+        /// );
+        /// ```
+        chrono::NaiveDateTime,
+        SnowflakeType::TimestampNtz,
+        "%Y-%m-%d %H:%M:%S%.9f"
+    );
+    // matches `YYYY-MM-DD HH24:MI:SS.FF9 TZH:TZM` from Snowflake's supported input formats,
+    // eg `2024-01-31 12:00:00.000000000 +00:00`
+    date_time_impl!(
+        chrono::DateTime<Utc>,
+        SnowflakeType::TimestampTz,
+        "%Y-%m-%d %H:%M:%S%.9f %:z"
+    );
+
+    // generic `Vec<T>` would overlap with `Vec<u8>`, which is bound as `BINARY`
+    macro_rules! vec_impl {
+        ($(#[$attr:meta])* $t:ty) => {
+            $(#[$attr])*
+            impl ToSql for Vec<$t> {
+                fn sql_type(&self) -> SnowflakeType {
+                    elements_sql_type(self)
+                }
+
+                fn null_sql_type() -> SnowflakeType {
+                    <$t>::null_sql_type()
+                }
+
+                fn to_sql(&self, out: &mut BytesMut) -> Result<IsNull, BindingError> {
+                    elements_to_json(self, out)
+                }
+
+                fn to_sql_multi(&self) -> Result<Option<Vec<Option<String>>>, BindingError> {
+                    elements_to_text(self).map(Some)
+                }
+
+                fn encode_format(&self) -> &'static str {
+                    elements_encode_format(self)
+                }
+            }
+        };
+    }
+
+    vec_impl!(chrono::NaiveDate);
+    vec_impl!(chrono::NaiveTime);
+    vec_impl!(chrono::NaiveDateTime);
+    vec_impl!(
+        /// Vectors of dates and times bind as arrays, the same way as [`super::Array`].
+        /// The array has a single type, every element is written in the same text format
+        /// as a single value of that type.
+        ///
+        /// ```rust
+        /// use chrono::{TimeZone, Utc};
+        /// use snowflake_api::bindings::ToSql;
+        /// use snowflake_api::{BindingValue, ParameterBinding, SnowflakeType};
+        ///
+        /// let timestamps = vec![
+        ///     Utc.with_ymd_and_hms(2024, 1, 31, 12, 0, 0).unwrap(),
+        ///     Utc.with_ymd_and_hms(2024, 2, 1, 0, 30, 0).unwrap(),
+        ///     Utc.timestamp_nanos(1_706_745_600_000_000_001),
+        /// ];
+        /// let binding = ParameterBinding::try_from(&timestamps as &dyn ToSql).unwrap();
+        /// assert_eq!(binding.type_, Some(SnowflakeType::TimestampTz));
+        /// assert_eq!(
+        ///     binding.value,
+        ///     Some(BindingValue::MultiBind(vec![
+        ///         Some("2024-01-31 12:00:00.000000000 +00:00".to_owned()),
+        ///         Some("2024-02-01 00:30:00.000000000 +00:00".to_owned()),
+        ///         Some("2024-02-01 00:00:00.000000001 +00:00".to_owned()),
+        ///     ]))
+        /// );
+        /// ```
+        chrono::DateTime<Utc>
+    );
+
+    /// Snowflake has no interval parameter type, durations are bound as a `FIXED` number of seconds.
+    /// Sub-second part is kept as a decimal fraction with up to nanosecond precision.
+    ///
+    /// ```rust
+    /// use snowflake_api::bindings::ToSql;
+    /// use snowflake_api::{BindingValue, ParameterBinding, SnowflakeType};
+    ///
+    /// let value: Box<dyn ToSql> = Box::new(chrono::Duration::minutes(90));
+    /// let binding = ParameterBinding::try_from(value).unwrap();
+    /// assert_eq!(binding.type_, Some(SnowflakeType::Fixed));
+    /// assert_eq!(binding.value, Some(BindingValue::SingleBind("5400".to_owned())));
+    ///
+    /// let value: Box<dyn ToSql> = Box::new(chrono::Duration::milliseconds(-1500));
+    /// let binding = ParameterBinding::try_from(value).unwrap();
+    /// assert_eq!(binding.value, Some(BindingValue::SingleBind("-1.5".to_owned())));
+    /// ```
+    impl ToSql for chrono::Duration {
+        fn sql_type(&self) -> SnowflakeType {
+            SnowflakeType::Fixed
+        }
+
+        fn null_sql_type() -> SnowflakeType {
+            SnowflakeType::Fixed
+        }
+
+        fn to_sql(&self, out: &mut BytesMut) -> Result<IsNull, BindingError> {
+            // both parts carry the sign of the duration
+            write_seconds(
+                out,
+                *self < chrono::Duration::zero(),
+                self.num_seconds().unsigned_abs(),
+                self.subsec_nanos().unsigned_abs(),
+            )?;
+            Ok(IsNull::No)
+        }
+
+        default_encode!();
+    }
+}
+
+#[cfg(feature = "rust_decimal")]
+mod rust_decimal_impls {
+    use std::fmt::Write;
+
+    use bytes::BytesMut;
+
+    use super::{write_scaled, BindingError, IsNull, Scaled, ToSql};
+    use crate::responses::SnowflakeType;
+
+    /// Decimals are written in plain notation with their scale preserved,
+    /// eg `Decimal::new(150, 2)` binds as `1.50`, not `1.5`
+    ///
+    /// ```rust
+    /// use rust_decimal::Decimal;
+    /// use snowflake_api::{BindingValue, ParameterBinding, SnowflakeType};
+    ///
+    /// let bound = |value: Decimal| ParameterBinding::from_value(value).unwrap().value;
+    /// let text = |s: &str| Some(BindingValue::SingleBind(s.to_owned()));
+    ///
+    /// assert_eq!(bound(Decimal::new(150, 2)), text("1.50"));
+    /// assert_eq!(bound(Decimal::new(-12_345, 2)), text("-123.45"));
+    /// assert_eq!(bound(Decimal::ZERO), text("0"));
+    /// assert_eq!(bound(Decimal::new(0, 3)), text("0.000"));
+    ///
+    /// // all 29 digits of the mantissa and up to 28 fractional digits
+    /// assert_eq!(bound(Decimal::MAX), text("79228162514264337593543950335"));
+    /// assert_eq!(bound(Decimal::MIN), text("-79228162514264337593543950335"));
+    /// assert_eq!(
+    ///     bound(Decimal::new(1, 28)),
+    ///     text("0.0000000000000000000000000001")
+    /// );
+    /// assert_eq!(
+    ///     bound(Decimal::from_i128_with_scale(-12_345_678_901_234_567_890_123_456_789, 28)),
+    ///     text("-1.2345678901234567890123456789")
+    /// );
+    ///
+    /// let binding = ParameterBinding::from_value(Decimal::ONE).unwrap();
+    /// assert_eq!(binding.type_, Some(SnowflakeType::Fixed));
+    /// ```
+    impl ToSql for rust_decimal::Decimal {
+        fn sql_type(&self) -> SnowflakeType {
+            SnowflakeType::Fixed
+        }
+
+        fn null_sql_type() -> SnowflakeType {
+            SnowflakeType::Fixed
+        }
+
+        fn to_sql(&self, out: &mut BytesMut) -> Result<IsNull, BindingError> {
+            write!(out, "{self}")?;
+            Ok(IsNull::No)
+        }
+
+        default_encode!();
+    }
+
+    /// Decimals rescaled to the given scale, see [`Scaled`]
+    ///
+    /// ```rust
+    /// use rust_decimal::Decimal;
+    /// use snowflake_api::bindings::{BindingError, Scaled};
+    /// use snowflake_api::{BindingValue, ParameterBinding, SnowflakeType};
+    ///
+    /// let bound = |value: Decimal, scale| {
+    ///     ParameterBinding::from_value(Scaled(value, scale)).map(|b| b.value)
+    /// };
+    /// let text = |s: &str| Some(BindingValue::SingleBind(s.to_owned()));
+    ///
+    /// // padded with zeros
+    /// assert_eq!(bound(Decimal::new(15, 1), 6).unwrap(), text("1.500000"));
+    /// assert_eq!(bound(Decimal::from(42), 2).unwrap(), text("42.00"));
+    /// assert_eq!(bound(Decimal::new(-5, 1), 3).unwrap(), text("-0.500"));
+    ///
+    /// // trailing zeros dropped
+    /// assert_eq!(bound(Decimal::new(1_500_000, 6), 2).unwrap(), text("1.50"));
+    /// assert_eq!(bound(Decimal::new(700, 2), 0).unwrap(), text("7"));
+    ///
+    /// // significant digits are never rounded away
+    /// let err = bound(Decimal::new(1505, 3), 2).unwrap_err();
+    /// assert!(matches!(err, BindingError::ScaleTruncation(v, 2) if v == "1.505"));
+    ///
+    /// let binding = ParameterBinding::from_value(Scaled(Decimal::ONE, 6)).unwrap();
+    /// assert_eq!(binding.type_, Some(SnowflakeType::Fixed));
+    /// ```
+    impl ToSql for Scaled<rust_decimal::Decimal> {
+        fn sql_type(&self) -> SnowflakeType {
+            SnowflakeType::Fixed
+        }
+
+        fn null_sql_type() -> SnowflakeType {
+            SnowflakeType::Fixed
+        }
+
+        fn to_sql(&self, out: &mut BytesMut) -> Result<IsNull, BindingError> {
+            write_scaled(out, &self.0.to_string(), self.1)?;
+            Ok(IsNull::No)
+        }
+
+        default_encode!();
+    }
+}
+
+#[cfg(feature = "time")]
+mod time_impls {
+    use bytes::{BufMut, BytesMut};
+    use time::format_description::BorrowedFormatItem;
+    use time::macros::format_description;
+
+    use super::{BindingError, IsNull, ToSql};
+    use crate::responses::SnowflakeType;
+
+    // same text formats as in the chrono bindings
+    const DATE: &[BorrowedFormatItem<'_>] = format_description!("[year]-[month]-[day]");
+    const TIME: &[BorrowedFormatItem<'_>] =
+        format_description!("[hour]:[minute]:[second].[subsecond digits:9]");
+    const PRIMITIVE_DATE_TIME: &[BorrowedFormatItem<'_>] =
+        format_description!("[year]-[month]-[day] [hour]:[minute]:[second].[subsecond digits:9]");
+    const OFFSET_DATE_TIME: &[BorrowedFormatItem<'_>] = format_description!(
+        "[year]-[month]-[day] [hour]:[minute]:[second].[subsecond digits:9] [offset_hour sign:mandatory]:[offset_minute]"
+    );
+
+    macro_rules! time_impl {
+        ($t:ty, $sql_type:expr, $format:expr) => {
+            impl ToSql for $t {
+                fn sql_type(&self) -> SnowflakeType {
+                    $sql_type
+                }
+
+                fn null_sql_type() -> SnowflakeType {
+                    $sql_type
+                }
+
+                fn to_sql(&self, out: &mut BytesMut) -> Result<IsNull, BindingError> {
+                    self.format_into(&mut out.writer(), $format)?;
+                    Ok(IsNull::No)
+                }
+
+                default_encode!();
+            }
+        };
+    }
+
+    time_impl!(time::Date, SnowflakeType::Date, DATE);
+    time_impl!(time::Time, SnowflakeType::Time, TIME);
+    time_impl!(
+        time::PrimitiveDateTime,
+        SnowflakeType::TimestampNtz,
+        PRIMITIVE_DATE_TIME
+    );
+    time_impl!(
+        time::OffsetDateTime,
+        SnowflakeType::TimestampTz,
+        OFFSET_DATE_TIME
+    );
+}
+
+#[cfg(feature = "uuid")]
+mod uuid_impls {
+    use std::fmt::Write;
+
+    use bytes::BytesMut;
+
+    use super::{BindingError, IsNull, ToSql};
+    use crate::responses::SnowflakeType;
+
+    /// Snowflake has no native UUID type, values are bound as `VARCHAR`
+    /// in their hyphenated lowercase form.
+    ///
+    /// ```rust
+    /// use snowflake_api::{BindingValue, ParameterBinding, SnowflakeType};
+    /// use uuid::Uuid;
+    ///
+    /// let id = Uuid::parse_str("67E55044-10B1-426F-9247-BB680E5FE0C8").unwrap();
+    /// let binding = ParameterBinding::from_value(id).unwrap();
+    /// assert_eq!(binding.type_, Some(SnowflakeType::Text));
+    /// assert_eq!(
+    ///     binding.value,
+    ///     Some(BindingValue::SingleBind("67e55044-10b1-426f-9247-bb680e5fe0c8".to_owned()))
+    /// );
+    ///
+    /// // references bind the same way
+    /// assert_eq!(ParameterBinding::from_value(&id).unwrap(), binding);
+    /// ```
+    impl ToSql for uuid::Uuid {
+        fn sql_type(&self) -> SnowflakeType {
+            SnowflakeType::Text
+        }
+
+        fn null_sql_type() -> SnowflakeType {
+            SnowflakeType::Text
+        }
+
+        fn to_sql(&self, out: &mut BytesMut) -> Result<IsNull, BindingError> {
+            write!(out, "{}", self.hyphenated())?;
+            Ok(IsNull::No)
+        }
+
+        default_encode!();
+    }
+}
+
+#[cfg(feature = "geo")]
+mod geo_impls {
+    use std::fmt::Write;
+
+    use bytes::BytesMut;
+    use wkt::ToWkt;
+
+    use super::{BindingError, Geography, Geometry, IsNull, ToSql};
+    use crate::responses::SnowflakeType;
+
+    macro_rules! geo_impl {
+        ($(#[$attr:meta])* [$t:ty $(, $rest:ty)*]) => {
+            geo_impl!($(#[$attr])* $t);
+            geo_impl!($(#[$attr])* [$($rest),*]);
+        };
+        ($(#[$attr:meta])* []) => {};
+        ($(#[$attr:meta])* $t:ty) => {
+            $(#[$attr])*
+            impl ToSql for $t {
+                fn sql_type(&self) -> SnowflakeType {
+                    SnowflakeType::Geography
+                }
+
+                fn null_sql_type() -> SnowflakeType {
+                    SnowflakeType::Geography
+                }
+
+                fn to_sql(&self, out: &mut BytesMut) -> Result<IsNull, BindingError> {
+                    write!(out, "{}", self.to_wkt())?;
+                    Ok(IsNull::No)
+                }
+
+                default_encode!();
+            }
+        };
+    }
+
+    geo_impl!(
+        /// Shapes are bound as `GEOGRAPHY` in their WKT text, with `x` as longitude and `y` as latitude.
+        /// Wrap them with [`Geometry::from_geo`] to bind them as `GEOMETRY` instead:
+        ///
+        /// ```rust
+        /// use snowflake_api::bindings::Geometry;
+        /// use snowflake_api::{BindingValue, ParameterBinding, SnowflakeType};
+        ///
+        /// let point = geo::Point::new(-122.35, 37.55);
+        /// let binding = ParameterBinding::from_value(point).unwrap();
+        /// assert_eq!(binding.type_, Some(SnowflakeType::Geography));
+        /// assert_eq!(
+        ///     binding.value,
+        ///     Some(BindingValue::SingleBind("POINT(-122.35 37.55)".to_owned()))
+        /// );
+        ///
+        /// let binding = ParameterBinding::from_value(Geometry::from_geo(&point)).unwrap();
+        /// assert_eq!(binding.type_, Some(SnowflakeType::Geometry));
+        /// assert_eq!(
+        ///     binding.value,
+        ///     Some(BindingValue::SingleBind("POINT(-122.35 37.55)".to_owned()))
+        /// );
+        /// ```
+        geo::Point<f64>
+    );
+
+    geo_impl!(
+        /// Bound as `GEOGRAPHY` in its WKT text, same as [`geo::Point`]
+        [
+            geo::Line<f64>,
+            geo::LineString<f64>,
+            geo::Polygon<f64>,
+            geo::MultiPoint<f64>,
+            geo::MultiLineString<f64>,
+            geo::MultiPolygon<f64>,
+            geo::GeometryCollection<f64>,
+            geo::Rect<f64>,
+            geo::Triangle<f64>
+        ]
+    );
+
+    geo_impl!(
+        /// Bound as `GEOGRAPHY` in the WKT text of the shape it holds, same as [`geo::Point`]
+        ///
+        /// ```rust
+        /// use geo::{line_string, Geometry};
+        /// use snowflake_api::{BindingValue, ParameterBinding, SnowflakeType};
+        ///
+        /// let route = Geometry::from(line_string![(x: 0.0, y: 0.0), (x: 1.5, y: 2.0)]);
+        /// let binding = ParameterBinding::from_value(route).unwrap();
+        /// assert_eq!(binding.type_, Some(SnowflakeType::Geography));
+        /// assert_eq!(
+        ///     binding.value,
+        ///     Some(BindingValue::SingleBind("LINESTRING(0 0,1.5 2)".to_owned()))
+        /// );
+        /// ```
+        geo::Geometry<f64>
+    );
+
+    impl Geography {
+        /// WKT text of a `geo` shape, eg to bind a [`geo::Geometry`] explicitly as `GEOGRAPHY`
+        pub fn from_geo(shape: &impl ToWkt<f64>) -> Self {
+            Self(shape.wkt_string())
+        }
+    }
+
+    impl Geometry {
+        /// WKT text of a `geo` shape, to bind it as `GEOMETRY` rather than `GEOGRAPHY`
+        pub fn from_geo(shape: &impl ToWkt<f64>) -> Self {
+            Self(shape.wkt_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::{self, Display, Write};
+
+    use super::{BindingError, BytesMut, IsNull, ToSql};
+    use crate::responses::SnowflakeType;
+    use crate::ParameterBinding;
+
+    /// Value whose text can't be written, as if the buffer refused it
+    struct Unwritable;
+
+    impl Display for Unwritable {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("partial")?;
+            Err(fmt::Error)
+        }
+    }
+
+    serializable_impl!(Unwritable, SnowflakeType::Text);
+
+    #[test]
+    fn failed_writes_are_errors() {
+        let mut out = BytesMut::new();
+        assert!(matches!(
+            Unwritable.to_sql(&mut out),
+            Err(BindingError::WriteError(_))
+        ));
+
+        let err = ParameterBinding::from_value(Unwritable).unwrap_err();
+        assert!(matches!(err, BindingError::WriteError(_)));
+        assert_eq!(err.to_string(), "Failed to write binding value");
+    }
+
+    #[cfg(all(feature = "chrono", feature = "time"))]
+    #[test]
+    fn time_bindings_match_chrono() {
+        use chrono::{NaiveDate, TimeZone, Utc};
+        use time::macros::{date, datetime, time};
+
+        use super::to_text;
+
+        let text = |value: &dyn ToSql| to_text(value).unwrap().unwrap();
+
+        let day = NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+        let chrono_time = day.and_hms_nano_opt(7, 5, 9, 1_002).unwrap();
+        assert_eq!(text(&date!(2024 - 02 - 29)), text(&day));
+        assert_eq!(
+            text(&time!(07:05:09.000_001_002)),
+            text(&chrono_time.time())
+        );
+        assert_eq!(
+            text(&datetime!(2024-02-29 07:05:09.000_001_002)),
+            text(&chrono_time)
+        );
+        assert_eq!(
+            text(&datetime!(2024-02-29 07:05:09.000_001_002 UTC)),
+            text(&Utc.from_utc_datetime(&chrono_time))
+        );
+        assert_eq!(
+            text(&datetime!(2024-02-29 07:05:09.000_001_002 UTC)),
+            "2024-02-29 07:05:09.000001002 +00:00"
+        );
+
+        // offsets are written with a mandatory sign, the same instant as above
+        assert_eq!(
+            text(&datetime!(2024-02-29 12:35:09.000_001_002 +05:30)),
+            "2024-02-29 12:35:09.000001002 +05:30"
+        );
+        assert_eq!(
+            text(&datetime!(2024-02-28 23:05:09.000_001_002 -08:00)),
+            "2024-02-28 23:05:09.000001002 -08:00"
+        );
+        assert_eq!(
+            datetime!(2024-02-28 23:05:09 UTC).sql_type(),
+            SnowflakeType::TimestampTz
+        );
+        assert_eq!(
+            datetime!(2024-02-28 23:05:09).sql_type(),
+            SnowflakeType::TimestampNtz
+        );
+    }
+}
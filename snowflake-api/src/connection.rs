@@ -1,13 +1,21 @@
+use flate2::write::GzEncoder;
+use flate2::Compression;
 use reqwest::header::{self, HeaderMap, HeaderName, HeaderValue};
+use reqwest::Method;
 use reqwest_middleware::ClientWithMiddleware;
 use reqwest_retry::policies::ExponentialBackoff;
-use reqwest_retry::RetryTransientMiddleware;
+use reqwest_retry::{Jitter, RetryDecision, RetryPolicy as _};
+use serde::Deserialize;
 use std::collections::HashMap;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::io::Write;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use thiserror::Error;
 use url::Url;
 use uuid::Uuid;
 
+use crate::transport::{Transport, TransportRequest, TransportResponse};
+
 #[derive(Error, Debug)]
 pub enum ConnectionError {
     #[error(transparent)]
@@ -24,6 +32,101 @@ pub enum ConnectionError {
 
     #[error(transparent)]
     InvalidHeader(#[from] header::InvalidHeaderValue),
+
+    #[error("Failed to compress request body: {0}")]
+    Compression(#[from] std::io::Error),
+
+    #[cfg(any(test, feature = "test-util"))]
+    #[error("No mocked response for `{0}`")]
+    NoMockedResponse(String),
+}
+
+/// Retry with exponential backoff for transient failures of HTTP requests:
+/// connection errors, timeouts, `408 Request Timeout`, `429 Too Many Requests` and `5xx` responses,
+/// as well as Snowflake errors with one of the [`RetryConfig::retry_codes`].
+/// SQL and authentication errors are returned by Snowflake as successful HTTP responses
+/// and are not retried unless their code is listed.
+///
+/// Retries are done by the [`Connection`], whatever its [`Transport`] is, so clients given to
+/// [`Connection::new_with_middware`] must not add a retry middleware of their own.
+/// Retried requests to Snowflake get a new `request_guid` and their `retryCount`,
+/// statements keep their `requestId`, so Snowflake returns the result of a statement
+/// it already received rather than executing it again.
+#[derive(Debug, Clone)]
+pub struct RetryConfig {
+    /// Retries after the first attempt, `0` disables retries
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled for every next one
+    pub base_delay: Duration,
+    /// Upper bound of the delay between retries
+    pub max_delay: Duration,
+    /// Randomize delays between zero and the computed backoff, to spread retries of concurrent clients
+    pub jitter: bool,
+    /// Snowflake error codes of transient failures, retried the same way as `5xx` responses.
+    /// Empty by default, codes of SQL errors shouldn't be listed, retrying them wouldn't help.
+    pub retry_codes: Vec<String>,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30 * 60),
+            jitter: true,
+            retry_codes: Vec::new(),
+        }
+    }
+}
+
+/// Code of a failed response, the body is deserialized into its actual type by the caller
+#[derive(Deserialize)]
+struct FailureCode {
+    success: bool,
+    code: Option<String>,
+}
+
+impl RetryConfig {
+    fn policy(&self) -> ExponentialBackoff {
+        ExponentialBackoff::builder()
+            .retry_bounds(self.base_delay, self.max_delay.max(self.base_delay))
+            .jitter(if self.jitter {
+                Jitter::Full
+            } else {
+                Jitter::None
+            })
+            .build_with_max_retries(self.max_retries)
+    }
+
+    fn is_transient_error(error: &ConnectionError) -> bool {
+        let (ConnectionError::RequestError(error)
+        | ConnectionError::RequestMiddlewareError(reqwest_middleware::Error::Reqwest(error))) =
+            error
+        else {
+            return false;
+        };
+        error.is_timeout() || error.is_connect()
+    }
+
+    fn is_transient_response(&self, resp: &TransportResponse, check_codes: bool) -> bool {
+        let status = resp.status;
+        if status.is_server_error()
+            || status == reqwest::StatusCode::REQUEST_TIMEOUT
+            || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+        {
+            return true;
+        }
+        if !check_codes || self.retry_codes.is_empty() {
+            return false;
+        }
+
+        serde_json::from_slice::<FailureCode>(&resp.body).is_ok_and(|failure| {
+            !failure.success
+                && failure
+                    .code
+                    .is_some_and(|code| self.retry_codes.contains(&code))
+        })
+    }
 }
 
 /// Container for query parameters
@@ -33,29 +136,43 @@ struct QueryContext {
     accept_mime: &'static str,
 }
 
+#[derive(Clone, Copy)]
 pub enum QueryType {
     LoginRequest,
+    AuthenticatorRequest,
     TokenRequest,
     CloseSession,
+    Heartbeat,
     JsonQuery,
     ArrowQuery,
+    QueryStatus,
+    QueryResult,
+    AbortQuery,
 }
 
 impl QueryType {
-    const fn query_context(&self) -> QueryContext {
+    const fn query_context(self) -> QueryContext {
         match self {
             Self::LoginRequest => QueryContext {
                 path: "session/v1/login-request",
                 accept_mime: "application/json",
             },
+            Self::AuthenticatorRequest => QueryContext {
+                path: "session/authenticator-request",
+                accept_mime: "application/json",
+            },
             Self::TokenRequest => QueryContext {
-                path: "/session/token-request",
+                path: "session/token-request",
                 accept_mime: "application/snowflake",
             },
             Self::CloseSession => QueryContext {
                 path: "session",
                 accept_mime: "application/snowflake",
             },
+            Self::Heartbeat => QueryContext {
+                path: "session/heartbeat",
+                accept_mime: "application/json",
+            },
             Self::JsonQuery => QueryContext {
                 path: "queries/v1/query-request",
                 accept_mime: "application/json",
@@ -64,15 +181,34 @@ impl QueryType {
                 path: "queries/v1/query-request",
                 accept_mime: "application/snowflake",
             },
+            Self::QueryStatus => QueryContext {
+                path: "monitoring/queries",
+                accept_mime: "application/json",
+            },
+            Self::QueryResult => QueryContext {
+                path: "queries",
+                accept_mime: "application/snowflake",
+            },
+            Self::AbortQuery => QueryContext {
+                path: "queries/v1/abort-request",
+                accept_mime: "application/json",
+            },
         }
     }
+
+    /// Requests executing queries, the only ones which can carry large bindings
+    const fn is_query(self) -> bool {
+        matches!(self, Self::JsonQuery | Self::ArrowQuery)
+    }
 }
 
 /// Connection pool
 /// Minimal session will have at least 2 requests - login and query
 pub struct Connection {
-    // no need for Arc as it's already inside the reqwest client
-    client: ClientWithMiddleware,
+    transport: Arc<dyn Transport>,
+    // bodies of query requests above this size are gzipped
+    compression_threshold: Option<usize>,
+    retry: RetryConfig,
 }
 
 impl Connection {
@@ -82,7 +218,9 @@ impl Connection {
         Ok(Self::new_with_middware(client.build()))
     }
 
-    /// Allow a user to provide their own middleware
+    /// Allow a user to provide their own middleware.
+    /// Requests are retried by the connection according to its [`RetryConfig`],
+    /// the client must not retry them as well, eg with `reqwest_retry::RetryTransientMiddleware`.
     ///
     /// Users can provide their own middleware to the connection like this:
     /// ```rust
@@ -93,12 +231,35 @@ impl Connection {
     /// ```
     /// This is not intended to be called directly, but is used by `SnowflakeApiBuilder::with_client`
     pub fn new_with_middware(client: ClientWithMiddleware) -> Self {
-        Self { client }
+        Self::with_transport(Arc::new(client))
     }
 
-    pub fn default_client_builder() -> Result<reqwest_middleware::ClientBuilder, ConnectionError> {
-        let retry_policy = ExponentialBackoff::builder().build_with_max_retries(3);
+    /// Connection sending requests through the given transport, eg a mock in tests
+    pub fn with_transport(transport: Arc<dyn Transport>) -> Self {
+        Self {
+            transport,
+            compression_threshold: None,
+            retry: RetryConfig::default(),
+        }
+    }
 
+    /// Gzip bodies of query requests larger than `threshold` bytes, eg with large bindings.
+    /// Smaller requests are sent as is, compressing them isn't worth the overhead.
+    #[must_use]
+    pub fn with_request_compression(mut self, threshold: usize) -> Self {
+        self.compression_threshold = Some(threshold);
+        self
+    }
+
+    /// Retry requests with the given configuration instead of the default one
+    #[must_use]
+    pub fn with_retry_config(mut self, retry: RetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// `reqwest` client for [`Connection::new_with_middware`], retries are done by the connection itself
+    pub fn default_client_builder() -> Result<reqwest_middleware::ClientBuilder, ConnectionError> {
         let client = reqwest::ClientBuilder::new()
             .user_agent("Rust/0.0.1")
             .gzip(true)
@@ -109,8 +270,79 @@ impl Connection {
 
         let client = client.build()?;
 
-        Ok(reqwest_middleware::ClientBuilder::new(client)
-            .with(RetryTransientMiddleware::new_with_policy(retry_policy)))
+        Ok(reqwest_middleware::ClientBuilder::new(client))
+    }
+
+    /// Send the request, retrying transient failures according to the [`RetryConfig`].
+    /// Snowflake error codes are only looked for in JSON responses of the API, `check_codes`.
+    async fn send(
+        &self,
+        request: TransportRequest,
+        check_codes: bool,
+    ) -> Result<TransportResponse, ConnectionError> {
+        let policy = self.retry.policy();
+        let start = SystemTime::now();
+        let mut retries = 0;
+        loop {
+            let attempt = if retries == 0 {
+                request.clone()
+            } else {
+                Self::retry_request(&request, retries)
+            };
+            let result = self.transport.send(attempt).await;
+            let transient = match &result {
+                Ok(resp) => self.retry.is_transient_response(resp, check_codes),
+                Err(e) => RetryConfig::is_transient_error(e),
+            };
+            if !transient {
+                return result;
+            }
+            let RetryDecision::Retry { execute_after } = policy.should_retry(start, retries) else {
+                return result;
+            };
+
+            let delay = execute_after
+                .duration_since(SystemTime::now())
+                .unwrap_or_default();
+            retries += 1;
+            log::debug!(
+                "Retrying request to {} in {delay:?}, attempt {}",
+                request.url.path(),
+                retries + 1
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Copy of the request for the given retry. Requests to Snowflake get a new `request_guid`
+    /// and their `retryCount`, while `requestId` is kept, other requests are sent unchanged.
+    fn retry_request(request: &TransportRequest, retry: u32) -> TransportRequest {
+        let mut request = request.clone();
+        if !request.url.query_pairs().any(|(k, _)| k == "request_guid") {
+            return request;
+        }
+
+        let request_guid = Uuid::new_v4().to_string();
+        let params: Vec<(String, String)> = request
+            .url
+            .query_pairs()
+            .filter(|(k, _)| k != "retryCount")
+            .map(|(k, v)| {
+                let v = if k == "request_guid" {
+                    request_guid.clone()
+                } else {
+                    v.into_owned()
+                };
+                (k.into_owned(), v)
+            })
+            .collect();
+        request
+            .url
+            .query_pairs_mut()
+            .clear()
+            .extend_pairs(params)
+            .append_pair("retryCount", &retry.to_string());
+        request
     }
 
     /// Perform request of given query type with extra body or parameters
@@ -123,17 +355,143 @@ impl Connection {
         extra_get_params: &[(&str, &str)],
         auth: Option<&str>,
         body: impl serde::Serialize,
+    ) -> Result<R, ConnectionError> {
+        self.request_with_id(
+            query_type,
+            account_identifier,
+            extra_get_params,
+            auth,
+            body,
+            Uuid::new_v4(),
+        )
+        .await
+    }
+
+    /// Same as [`Connection::request`], but with the caller provided `requestId`,
+    /// which can be used to abort the query later
+    pub async fn request_with_id<R: serde::de::DeserializeOwned>(
+        &self,
+        query_type: QueryType,
+        account_identifier: &str,
+        extra_get_params: &[(&str, &str)],
+        auth: Option<&str>,
+        body: impl serde::Serialize,
+        request_id: Uuid,
     ) -> Result<R, ConnectionError> {
         let context = query_type.query_context();
+        let (url, headers) = Self::prepare(
+            &context,
+            account_identifier,
+            None,
+            extra_get_params,
+            auth,
+            request_id,
+        )?;
+
+        let mut headers = headers;
+        headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/json"),
+        );
+        let mut body = serde_json::to_vec(&body)?;
+        if query_type.is_query() && self.compression_threshold.is_some_and(|t| body.len() > t) {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(&body)?;
+            body = encoder.finish()?;
+            headers.insert(header::CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+        }
+
+        let resp = self
+            .send(
+                TransportRequest {
+                    method: Method::POST,
+                    url,
+                    headers,
+                    body: Some(body.into()),
+                },
+                true,
+            )
+            .await?;
+
+        Ok(serde_json::from_slice(&resp.body)?)
+    }
 
-        let request_id = Uuid::new_v4();
+    /// POST JSON body to a URL outside of the Snowflake API, eg of an identity provider
+    pub async fn post_url<R: serde::de::DeserializeOwned>(
+        &self,
+        url: &str,
+        body: impl serde::Serialize,
+    ) -> Result<R, ConnectionError> {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, HeaderValue::from_static("application/json"));
+        headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/json"),
+        );
+        let resp = self
+            .send(
+                TransportRequest {
+                    method: Method::POST,
+                    url: Url::parse(url)?,
+                    headers,
+                    body: Some(serde_json::to_vec(&body)?.into()),
+                },
+                false,
+            )
+            .await?;
+
+        Ok(serde_json::from_slice(&resp.body)?)
+    }
+
+    /// Fetch resource of given query type, eg status or result of the query with given id
+    pub async fn get<R: serde::de::DeserializeOwned>(
+        &self,
+        query_type: QueryType,
+        account_identifier: &str,
+        resource: &str,
+        auth: Option<&str>,
+    ) -> Result<R, ConnectionError> {
+        let context = query_type.query_context();
+        let (url, headers) = Self::prepare(
+            &context,
+            account_identifier,
+            Some(resource),
+            &[],
+            auth,
+            Uuid::new_v4(),
+        )?;
+
+        let resp = self
+            .send(
+                TransportRequest {
+                    method: Method::GET,
+                    url,
+                    headers,
+                    body: None,
+                },
+                true,
+            )
+            .await?;
+
+        Ok(serde_json::from_slice(&resp.body)?)
+    }
+
+    fn prepare(
+        context: &QueryContext,
+        account_identifier: &str,
+        resource: Option<&str>,
+        extra_get_params: &[(&str, &str)],
+        auth: Option<&str>,
+        request_id: Uuid,
+    ) -> Result<(Url, HeaderMap), ConnectionError> {
         let request_guid = Uuid::new_v4();
         let client_start_time = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap()
             .as_secs()
             .to_string();
-        // fixme: update uuid's on the retry
+        // `requestId` identifies the statement and is kept across retries for deduplication,
+        // `request_guid` identifies the HTTP request, retries get a new one in `Connection::send`
         let request_id = request_id.to_string();
         let request_guid = request_guid.to_string();
 
@@ -144,10 +502,14 @@ impl Connection {
         ];
         get_params.extend_from_slice(extra_get_params);
 
-        let url = format!(
+        let mut url = format!(
             "https://{}.snowflakecomputing.com/{}",
             &account_identifier, context.path
         );
+        if let Some(resource) = resource {
+            url.push('/');
+            url.push_str(resource);
+        }
         let url = Url::parse_with_params(&url, get_params)?;
 
         let mut headers = HeaderMap::new();
@@ -162,16 +524,7 @@ impl Connection {
             headers.append(header::AUTHORIZATION, auth_val);
         }
 
-        // todo: persist client to use connection polling
-        let resp = self
-            .client
-            .post(url)
-            .headers(headers)
-            .json(&body)
-            .send()
-            .await?;
-
-        Ok(resp.json::<R>().await?)
+        Ok((url, headers))
     }
 
     pub async fn get_chunk(
@@ -186,14 +539,199 @@ impl Connection {
                 HeaderValue::from_bytes(v.as_bytes()).unwrap(),
             );
         }
-        let bytes = self
-            .client
-            .get(url)
-            .headers(header_map)
-            .send()
-            .await?
-            .bytes()
+        let resp = self
+            .send(
+                TransportRequest {
+                    method: Method::GET,
+                    url: Url::parse(url)?,
+                    headers: header_map,
+                    body: None,
+                },
+                false,
+            )
             .await?;
-        Ok(bytes)
+        Ok(resp.body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use reqwest::StatusCode;
+    use serde_json::json;
+
+    use super::RetryConfig;
+    use crate::bindings::Array;
+    use crate::transport::{MockTransport, TransportRequest, TransportResponse};
+    use crate::{AuthArgs, ExecRequestBuilder, SnowflakeApiBuilder, SnowflakeApiError};
+
+    const QUERY_PATH: &str = "/queries/v1/query-request";
+
+    fn param(request: &TransportRequest, name: &str) -> Option<String> {
+        request
+            .url
+            .query_pairs()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.into_owned())
+    }
+
+    fn query_attempts(mock: &MockTransport) -> Vec<TransportRequest> {
+        mock.requests()
+            .into_iter()
+            .filter(|r| r.url.path() == QUERY_PATH)
+            .collect()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn retries_transient_failures() {
+        let mock = MockTransport::new();
+        mock.push_login();
+        mock.push_response(
+            QUERY_PATH,
+            TransportResponse {
+                status: StatusCode::SERVICE_UNAVAILABLE,
+                body: "".into(),
+            },
+        );
+        mock.push_json(
+            QUERY_PATH,
+            &json!({ "code": "390000", "message": "Service unavailable", "success": false, "data": null }),
+        );
+        mock.push_query_result(json!([]), json!([]));
+        let retry = RetryConfig {
+            max_retries: 3,
+            base_delay: Duration::from_millis(100),
+            retry_codes: vec!["390000".to_owned()],
+            ..RetryConfig::default()
+        };
+        let api = SnowflakeApiBuilder::new(AuthArgs::for_tests())
+            .with_transport(mock.clone())
+            .with_retry_config(retry)
+            .build()
+            .unwrap();
+
+        api.exec("SELECT 1").await.unwrap();
+
+        let attempts = query_attempts(&mock);
+        assert_eq!(attempts.len(), 3);
+        // the statement is deduplicated by its `requestId`, each HTTP request has its own guid
+        assert!(attempts
+            .iter()
+            .all(|r| param(r, "requestId") == param(&attempts[0], "requestId")));
+        assert_ne!(
+            param(&attempts[0], "request_guid"),
+            param(&attempts[1], "request_guid")
+        );
+        assert_ne!(
+            param(&attempts[1], "request_guid"),
+            param(&attempts[2], "request_guid")
+        );
+        let retry_counts: Vec<_> = attempts.iter().map(|r| param(r, "retryCount")).collect();
+        assert_eq!(
+            retry_counts,
+            [None, Some("1".to_owned()), Some("2".to_owned())]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn gives_up_after_max_retries() {
+        let mock = MockTransport::new();
+        mock.push_login();
+        for _ in 0..3 {
+            mock.push_response(
+                QUERY_PATH,
+                TransportResponse {
+                    status: StatusCode::TOO_MANY_REQUESTS,
+                    body: "".into(),
+                },
+            );
+        }
+        let retry = RetryConfig {
+            max_retries: 2,
+            ..RetryConfig::default()
+        };
+        let api = SnowflakeApiBuilder::new(AuthArgs::for_tests())
+            .with_transport(mock.clone())
+            .with_retry_config(retry)
+            .build()
+            .unwrap();
+
+        assert!(api.exec("SELECT 1").await.is_err());
+        assert_eq!(query_attempts(&mock).len(), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn sql_errors_are_not_retried() {
+        let mock = MockTransport::new();
+        mock.push_login();
+        mock.push_json(
+            QUERY_PATH,
+            &json!({
+                "code": "002003",
+                "message": "Object 'MISSING' does not exist or not authorized.",
+                "success": false,
+                "data": {
+                    "age": 0,
+                    "errorCode": "002003",
+                    "internalError": false,
+                    "sqlState": "42S02",
+                    "queryId": "01b2c3d4"
+                }
+            }),
+        );
+        let api = SnowflakeApiBuilder::new(AuthArgs::for_tests())
+            .with_transport(mock.clone())
+            .build()
+            .unwrap();
+
+        let Err(SnowflakeApiError::SnowflakeError { code, .. }) =
+            api.exec("SELECT * FROM MISSING").await
+        else {
+            panic!("expected a Snowflake error");
+        };
+        assert_eq!(code, "002003");
+        assert_eq!(query_attempts(&mock).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn compresses_large_query_requests() {
+        let mock = MockTransport::new();
+        mock.push_login();
+        for _ in 0..2 {
+            mock.push_query_result(json!([]), json!([]));
+        }
+        let api = SnowflakeApiBuilder::new(AuthArgs::for_tests())
+            .with_request_compression(16 * 1024)
+            .with_transport(mock.clone())
+            .build()
+            .unwrap();
+
+        let ids: Vec<i64> = (0..10_000).collect();
+        let large = ExecRequestBuilder::new()
+            .sql("INSERT INTO ORDERS (ID) VALUES (?)")
+            .bind("1", Array::from(ids))
+            .unwrap();
+        api.exec_request(large).await.unwrap();
+        let small = ExecRequestBuilder::new()
+            .sql("DELETE FROM ORDERS WHERE ID = ?")
+            .bind("1", 42)
+            .unwrap();
+        api.exec_request(small).await.unwrap();
+
+        let requests = mock.requests();
+        assert!(requests[1].is_gzipped());
+        let body = requests[1].json_body().unwrap();
+        assert_eq!(
+            body["bindings"]["1"]["value"].as_array().unwrap().len(),
+            10_000
+        );
+        assert!(requests[1].body.as_ref().unwrap().len() < body.to_string().len());
+
+        assert!(!requests[2].is_gzipped());
+        assert_eq!(
+            requests[2].json_body().unwrap()["bindings"]["1"]["value"],
+            "42"
+        );
     }
 }
@@ -0,0 +1,302 @@
+//! Mapping of result rows into structs with serde, see [`crate::SnowflakeApi::query_as`],
+//! and [`RowStreamExt`] for streamed results.
+//!
+//! Every row is deserialized from a map of column names to values, converted according to
+//! the column type: numbers, booleans and semi-structured values keep their JSON types,
+//! everything else is a string. Unquoted identifiers are returned by Snowflake in upper case,
+//! so by default names are lower-cased to match Rust field names, see [`RenameRule`].
+//!
+//! ```rust
+//! use serde::Deserialize;
+//! use serde_json::json;
+//! use snowflake_api::de::RenameRule;
+//! use snowflake_api::{FieldSchema, JsonResult, QueryResult, SnowflakeType};
+//!
+//! let field = |name: &str, type_| FieldSchema {
+//!     name: name.to_owned(),
+//!     type_,
+//!     scale: Some(0),
+//!     precision: None,
+//!     length: None,
+//!     nullable: true,
+//! };
+//! let result = QueryResult::Json(JsonResult {
+//!     value: json!([["1", "apple"], ["2", null]]),
+//!     schema: vec![
+//!         field("ORDER_ID", SnowflakeType::Fixed),
+//!         field("PRODUCT_NAME", SnowflakeType::Text),
+//!     ],
+//! });
+//!
+//! #[derive(Deserialize, Debug, PartialEq)]
+//! struct Order {
+//!     order_id: i64,
+//!     #[serde(rename = "product_name")]
+//!     product: Option<String>,
+//! }
+//!
+//! let orders: Vec<Order> = result.deserialize_rows(RenameRule::Lowercase).unwrap();
+//! assert_eq!(
+//!     orders,
+//!     [
+//!         Order { order_id: 1, product: Some("apple".to_owned()) },
+//!         Order { order_id: 2, product: None },
+//!     ]
+//! );
+//!
+//! #[derive(Deserialize, Debug, PartialEq)]
+//! #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+//! struct OrderId {
+//!     order_id: u32,
+//! }
+//!
+//! let ids: Vec<OrderId> = result.deserialize_rows(RenameRule::AsIs).unwrap();
+//! assert_eq!(ids, [OrderId { order_id: 1 }, OrderId { order_id: 2 }]);
+//! ```
+
+use arrow::record_batch::RecordBatch;
+use futures::stream::{self, BoxStream, StreamExt, TryStreamExt};
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+use crate::arrow_types::{text_rows, to_logical_types};
+use crate::responses::SnowflakeType;
+use crate::{FieldSchema, QueryResult, RecordBatchStream, SnowflakeApiError};
+
+/// How column names are matched with field names
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RenameRule {
+    /// Column names are used as returned, eg with `#[serde(rename_all = "SCREAMING_SNAKE_CASE")]`
+    AsIs,
+    /// Column names are lower-cased, `ORDER_ID` matches `order_id`
+    #[default]
+    Lowercase,
+}
+
+impl RenameRule {
+    fn apply(self, name: &str) -> String {
+        match self {
+            Self::AsIs => name.to_owned(),
+            Self::Lowercase => name.to_lowercase(),
+        }
+    }
+}
+
+impl QueryResult {
+    /// Deserialize every row into `T`, see the [module documentation](crate::de)
+    pub fn deserialize_rows<T: DeserializeOwned>(
+        &self,
+        rename: RenameRule,
+    ) -> Result<Vec<T>, SnowflakeApiError> {
+        let columns = self.columns()?;
+        let names = column_names(&columns, rename);
+
+        let rows = match self {
+            QueryResult::Empty => return Ok(Vec::new()),
+            QueryResult::Json(json) => json
+                .value
+                .as_array()
+                .map(Vec::as_slice)
+                .unwrap_or_default()
+                .iter()
+                .filter_map(Value::as_array)
+                .map(|values| {
+                    let values = columns
+                        .iter()
+                        .zip(values)
+                        .map(|(field, value)| match value {
+                            Value::String(raw) => typed_value(field, raw),
+                            other => other.clone(),
+                        })
+                        .collect();
+                    row(&names, values)
+                })
+                .collect::<Result<_, _>>()?,
+            QueryResult::Arrow(batches) => arrow_rows(&columns, &names, batches)?,
+        };
+        Ok(rows)
+    }
+}
+
+/// Rows of a streamed result deserialized into `T` as the batches arrive,
+/// only the rows of the batch being read are kept in memory
+pub trait RowStreamExt {
+    /// Deserialize every row into `T`, column names are lower-cased, see [`RenameRule`]
+    fn rows_as<T: DeserializeOwned + Send + 'static>(
+        self,
+    ) -> BoxStream<'static, Result<T, SnowflakeApiError>>
+    where
+        Self: Sized,
+    {
+        self.rows_as_with(RenameRule::default())
+    }
+
+    /// Same as [`RowStreamExt::rows_as`], with the given mapping of column names
+    fn rows_as_with<T: DeserializeOwned + Send + 'static>(
+        self,
+        rename: RenameRule,
+    ) -> BoxStream<'static, Result<T, SnowflakeApiError>>;
+}
+
+impl RowStreamExt for RecordBatchStream {
+    fn rows_as_with<T: DeserializeOwned + Send + 'static>(
+        self,
+        rename: RenameRule,
+    ) -> BoxStream<'static, Result<T, SnowflakeApiError>> {
+        self.and_then(move |batch| async move {
+            let columns = batch
+                .schema()
+                .fields()
+                .iter()
+                .map(|f| FieldSchema::try_from(f.as_ref()))
+                .collect::<Result<Vec<_>, _>>()?;
+            let names = column_names(&columns, rename);
+            arrow_rows(&columns, &names, std::slice::from_ref(&batch))
+        })
+        .map_ok(|rows: Vec<T>| stream::iter(rows.into_iter().map(Ok)))
+        .try_flatten()
+        .boxed()
+    }
+}
+
+fn column_names(columns: &[FieldSchema], rename: RenameRule) -> Vec<String> {
+    columns.iter().map(|c| rename.apply(&c.name)).collect()
+}
+
+fn row<T: DeserializeOwned>(names: &[String], values: Vec<Value>) -> Result<T, serde_json::Error> {
+    let object: Map<_, _> = names.iter().cloned().zip(values).collect();
+    serde_json::from_value(Value::Object(object))
+}
+
+fn arrow_rows<T: DeserializeOwned>(
+    columns: &[FieldSchema],
+    names: &[String],
+    batches: &[RecordBatch],
+) -> Result<Vec<T>, SnowflakeApiError> {
+    // text of the logical types, eg `1.50` rather than the unscaled `150`
+    let batches = batches
+        .iter()
+        .map(to_logical_types)
+        .collect::<Result<Vec<_>, _>>()?;
+    let (_, rows) = text_rows(&batches)?;
+    let rows = rows
+        .into_iter()
+        .map(|values| {
+            let values = columns
+                .iter()
+                .zip(values)
+                .map(|(field, raw)| raw.map_or(Value::Null, |raw| typed_value(field, &raw)))
+                .collect();
+            row(names, values)
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(rows)
+}
+
+/// JSON value matching the column type, text is kept if it doesn't parse
+fn typed_value(field: &FieldSchema, raw: &str) -> Value {
+    let parsed = match field.type_ {
+        SnowflakeType::Fixed | SnowflakeType::Real => raw.parse().ok().map(Value::Number),
+        SnowflakeType::Boolean => match raw {
+            "1" => Some(Value::Bool(true)),
+            "0" => Some(Value::Bool(false)),
+            _ => raw.to_ascii_lowercase().parse().ok().map(Value::Bool),
+        },
+        SnowflakeType::Variant | SnowflakeType::Object | SnowflakeType::Array => {
+            serde_json::from_str(raw).ok()
+        }
+        _ => None,
+    };
+    parsed.unwrap_or_else(|| Value::String(raw.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use arrow::array::{ArrayRef, Int64Array, StringArray};
+    use arrow::datatypes::{DataType, Field, Schema};
+    use arrow::ipc::writer::StreamWriter;
+    use arrow::record_batch::RecordBatch;
+    use base64::Engine;
+    use futures::TryStreamExt;
+    use reqwest::StatusCode;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    use super::RowStreamExt;
+    use crate::transport::{MockTransport, TransportResponse};
+    use crate::{AuthArgs, SnowflakeApiBuilder};
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Order {
+        order_id: i64,
+        product: String,
+    }
+
+    fn field(name: &str, data_type: DataType, logical_type: &str) -> Field {
+        let metadata = HashMap::from([
+            ("logicalType".to_owned(), logical_type.to_owned()),
+            ("scale".to_owned(), "0".to_owned()),
+        ]);
+        Field::new(name, data_type, true).with_metadata(metadata)
+    }
+
+    fn ipc(schema: &Arc<Schema>, ids: Vec<i64>, products: Vec<&str>) -> Vec<u8> {
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(Int64Array::from(ids)),
+            Arc::new(StringArray::from(products)),
+        ];
+        let batch = RecordBatch::try_new(Arc::clone(schema), columns).unwrap();
+        let mut writer = StreamWriter::try_new(Vec::new(), schema).unwrap();
+        writer.write(&batch).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    #[tokio::test]
+    async fn rows_of_chunks_are_deserialized_as_they_are_read() {
+        let schema = Arc::new(Schema::new(vec![
+            field("ORDER_ID", DataType::Int64, "FIXED"),
+            field("PRODUCT", DataType::Utf8, "TEXT"),
+        ]));
+        let mock = MockTransport::new();
+        mock.push_login();
+        let mut result = MockTransport::query_result(json!([]), json!(null));
+        result["data"]["rowsetBase64"] = json!(base64::engine::general_purpose::STANDARD
+            .encode(ipc(&schema, vec![1, 2], vec!["apple", "banana"])));
+        result["data"]["total"] = json!(3);
+        result["data"]["returned"] = json!(2);
+        result["data"]["chunks"] = json!([
+            { "url": "https://stage.example.com/chunk0", "rowCount": 1, "uncompressedSize": 100 }
+        ]);
+        mock.push_json("/queries/v1/query-request", &result);
+        mock.push_response(
+            "/chunk0",
+            TransportResponse {
+                status: StatusCode::OK,
+                body: ipc(&schema, vec![3], vec!["cherry"]).into(),
+            },
+        );
+        let api = SnowflakeApiBuilder::new(AuthArgs::for_tests())
+            .with_transport(mock.clone())
+            .build()
+            .unwrap();
+
+        let mut orders = api
+            .exec_stream("SELECT ORDER_ID, PRODUCT FROM ORDERS")
+            .await
+            .unwrap()
+            .rows_as::<Order>();
+        let order = |order_id, product: &str| Order {
+            order_id,
+            product: product.to_owned(),
+        };
+        assert_eq!(orders.try_next().await.unwrap(), Some(order(1, "apple")));
+        assert_eq!(orders.try_next().await.unwrap(), Some(order(2, "banana")));
+        // the chunk is only downloaded once its rows are read
+        assert_eq!(mock.requests().len(), 2);
+        assert_eq!(orders.try_next().await.unwrap(), Some(order(3, "cherry")));
+        assert_eq!(orders.try_next().await.unwrap(), None);
+    }
+}
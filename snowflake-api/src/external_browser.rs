@@ -0,0 +1,205 @@
+//! Local side of the external browser SSO flow.
+//!
+//! Snowflake returns an SSO URL of the identity provider, which redirects the browser back
+//! to `localhost` once the user has logged in. The token is passed either in the query string
+//! of a `GET` request or in the form encoded body of a `POST` request.
+
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::process::{Command, Stdio};
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Upper bound of the callback request size, tokens are much smaller
+const MAX_REQUEST_SIZE: usize = 64 * 1024;
+
+const RESPONSE_BODY: &str = "<!DOCTYPE html><html><head><title>Snowflake</title></head>\
+    <body>Your identity was confirmed and propagated to the application. \
+    You can close this window now.</body></html>";
+
+pub struct CallbackListener {
+    listener: TcpListener,
+}
+
+impl CallbackListener {
+    /// Listen on the loopback interface, port `0` picks any free port
+    pub async fn bind(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind(SocketAddr::from((Ipv4Addr::LOCALHOST, port))).await?;
+        Ok(Self { listener })
+    }
+
+    pub fn port(&self) -> io::Result<u16> {
+        Ok(self.listener.local_addr()?.port())
+    }
+
+    /// Accept connections until one of them carries the token.
+    /// CORS preflight and unrelated requests, eg for `favicon.ico`, are answered and skipped.
+    pub async fn wait_for_token(&self) -> io::Result<String> {
+        loop {
+            let (mut stream, _) = self.listener.accept().await?;
+            match handle_connection(&mut stream).await {
+                Ok(Some(token)) => return Ok(token),
+                Ok(None) => {}
+                Err(e) => log::debug!("Failed to handle browser callback: {e}"),
+            }
+        }
+    }
+}
+
+async fn handle_connection(stream: &mut TcpStream) -> io::Result<Option<String>> {
+    let request = read_request(stream).await?;
+
+    if request.method == "OPTIONS" {
+        let allow_headers = request
+            .header("access-control-request-headers")
+            .unwrap_or("Content-Type");
+        let headers = format!(
+            "Access-Control-Allow-Origin: {}\r\n\
+             Access-Control-Allow-Methods: GET, POST\r\n\
+             Access-Control-Allow-Headers: {allow_headers}\r\n",
+            request.header("origin").unwrap_or("*"),
+        );
+        write_response(stream, "200 OK", &headers, "").await?;
+        return Ok(None);
+    }
+
+    let token = request.token();
+    if token.is_some() {
+        let headers = request
+            .header("origin")
+            .map(|origin| format!("Access-Control-Allow-Origin: {origin}\r\n"))
+            .unwrap_or_default();
+        write_response(stream, "200 OK", &headers, RESPONSE_BODY).await?;
+    } else {
+        write_response(stream, "400 Bad Request", "", "").await?;
+    }
+    Ok(token)
+}
+
+struct CallbackRequest {
+    method: String,
+    target: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl CallbackRequest {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn token(&self) -> Option<String> {
+        let query = self.target.split_once('?').map(|(_, q)| q.as_bytes());
+        let form = (self.method == "POST").then_some(self.body.as_slice());
+
+        [query, form]
+            .into_iter()
+            .flatten()
+            .flat_map(url::form_urlencoded::parse)
+            .find(|(name, _)| name == "token")
+            .map(|(_, value)| value.into_owned())
+            .filter(|token| !token.is_empty())
+    }
+}
+
+async fn read_request(stream: &mut TcpStream) -> io::Result<CallbackRequest> {
+    let mut buf = Vec::new();
+    let header_end = loop {
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos;
+        }
+        read_more(stream, &mut buf).await?;
+    };
+
+    let head = String::from_utf8_lossy(&buf[..header_end]).into_owned();
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or_default().split(' ');
+    let method = request_line.next().unwrap_or_default().to_owned();
+    let target = request_line.next().unwrap_or_default().to_owned();
+    let headers: Vec<_> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_owned(), value.trim().to_owned()))
+        .collect();
+
+    let content_length = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.parse::<usize>().ok())
+        .unwrap_or(0);
+    let body_start = header_end + 4;
+    while buf.len() < body_start + content_length {
+        read_more(stream, &mut buf).await?;
+    }
+
+    Ok(CallbackRequest {
+        method,
+        target,
+        headers,
+        body: buf[body_start..body_start + content_length].to_vec(),
+    })
+}
+
+async fn read_more(stream: &mut TcpStream, buf: &mut Vec<u8>) -> io::Result<()> {
+    let mut chunk = [0; 4096];
+    let read = stream.read(&mut chunk).await?;
+    if read == 0 {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    if buf.len() + read > MAX_REQUEST_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Browser callback request is too large",
+        ));
+    }
+    buf.extend_from_slice(&chunk[..read]);
+    Ok(())
+}
+
+async fn write_response(
+    stream: &mut TcpStream,
+    status: &str,
+    headers: &str,
+    body: &str,
+) -> io::Result<()> {
+    let response = format!(
+        "HTTP/1.1 {status}\r\n\
+         Content-Type: text/html\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\
+         {headers}\r\n\
+         {body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+/// Opens the URL with the default browser of the platform.
+/// The URL is logged if the browser can't be started, so it can be opened manually.
+pub fn open_browser(url: &str) {
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(target_os = "windows") {
+        let mut command = Command::new("rundll32");
+        command.arg("url.dll,FileProtocolHandler");
+        command
+    } else {
+        Command::new("xdg-open")
+    };
+
+    let spawned = command
+        .arg(url)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    if let Err(e) = spawned {
+        log::warn!(
+            "Failed to open the browser: {e}, open the following URL in the browser to authenticate: {url}"
+        );
+    }
+}
@@ -0,0 +1,356 @@
+//! Conversion of result values into Rust types, counterpart of [`crate::bindings::ToSql`].
+//!
+//! Values are parsed from their text representation, as returned in JSON result sets.
+//!
+//! ```rust
+//! use snowflake_api::bindings::ToSql;
+//! use snowflake_api::from_sql::FromSql;
+//! use snowflake_api::{BindingValue, ParameterBinding};
+//!
+//! let value: Box<dyn ToSql> = Box::new(42i64);
+//! let binding = ParameterBinding::try_from(value).unwrap();
+//! let Some(BindingValue::SingleBind(raw)) = binding.value else {
+//!     unreachable!()
+//! };
+//! let ty = binding.type_.unwrap();
+//! assert_eq!(i64::from_sql(&ty, &raw).unwrap(), 42);
+//! ```
+
+use thiserror::Error;
+
+use crate::responses::SnowflakeType;
+use crate::FieldSchema;
+
+#[derive(Error, Debug)]
+pub enum FromSqlError {
+    #[error("Can not convert value of {0:?} type into `{1}`")]
+    WrongType(SnowflakeType, &'static str),
+
+    #[error("Invalid {0:?} value: `{1}`")]
+    InvalidValue(SnowflakeType, String),
+
+    #[error("Unexpected NULL value for `{0}`")]
+    UnexpectedNull(&'static str),
+
+    #[error("Column index {0} is out of bounds for a row of {1} columns")]
+    OutOfBounds(usize, usize),
+
+    #[error("Column `{0}` is not present in the result")]
+    UnknownColumn(String),
+}
+
+/// A type which can be created from a result value
+pub trait FromSql: Sized {
+    /// Whether values of the given column type naturally convert into this type
+    fn accepts(ty: &SnowflakeType) -> bool;
+
+    /// Parses text representation of the value.
+    /// Conversion is best-effort, eg numbers can be read from `TEXT` columns if they parse.
+    fn from_sql(ty: &SnowflakeType, raw: &str) -> Result<Self, FromSqlError>;
+
+    /// Called for NULL values, only nullable types can be created from them
+    fn from_sql_null(_ty: &SnowflakeType) -> Result<Self, FromSqlError> {
+        Err(FromSqlError::UnexpectedNull(std::any::type_name::<Self>()))
+    }
+
+    /// Parses a nullable value
+    fn from_sql_nullable(ty: &SnowflakeType, raw: Option<&str>) -> Result<Self, FromSqlError> {
+        match raw {
+            Some(raw) => Self::from_sql(ty, raw),
+            None => Self::from_sql_null(ty),
+        }
+    }
+
+    /// Parses a nullable value of the column, types which carry a scale override this
+    /// to match the scale of `NUMBER(p, s)` columns
+    fn from_sql_field(field: &FieldSchema, raw: Option<&str>) -> Result<Self, FromSqlError> {
+        Self::from_sql_nullable(&field.type_, raw)
+    }
+}
+
+/// Error for a value which failed to convert
+fn conversion_error<T: FromSql>(ty: SnowflakeType, raw: &str) -> FromSqlError {
+    if T::accepts(&ty) {
+        FromSqlError::InvalidValue(ty, raw.to_owned())
+    } else {
+        FromSqlError::WrongType(ty, std::any::type_name::<T>())
+    }
+}
+
+macro_rules! parseable_impl {
+    ($t:ty, $($accepts:pat_param)|+) => {
+        impl FromSql for $t {
+            fn accepts(ty: &SnowflakeType) -> bool {
+                matches!(ty, $($accepts)|+)
+            }
+
+            fn from_sql(ty: &SnowflakeType, raw: &str) -> Result<Self, FromSqlError> {
+                raw.parse().map_err(|_| conversion_error::<Self>(*ty, raw))
+            }
+        }
+    };
+}
+
+parseable_impl!(i8, SnowflakeType::Fixed);
+parseable_impl!(i16, SnowflakeType::Fixed);
+parseable_impl!(i32, SnowflakeType::Fixed);
+parseable_impl!(i64, SnowflakeType::Fixed);
+parseable_impl!(i128, SnowflakeType::Fixed);
+parseable_impl!(isize, SnowflakeType::Fixed);
+parseable_impl!(u8, SnowflakeType::Fixed);
+parseable_impl!(u16, SnowflakeType::Fixed);
+parseable_impl!(u32, SnowflakeType::Fixed);
+parseable_impl!(u64, SnowflakeType::Fixed);
+parseable_impl!(u128, SnowflakeType::Fixed);
+parseable_impl!(usize, SnowflakeType::Fixed);
+parseable_impl!(f32, SnowflakeType::Real | SnowflakeType::Fixed);
+parseable_impl!(f64, SnowflakeType::Real | SnowflakeType::Fixed);
+parseable_impl!(char, SnowflakeType::Text);
+
+impl FromSql for String {
+    fn accepts(ty: &SnowflakeType) -> bool {
+        matches!(ty, SnowflakeType::Text)
+    }
+
+    /// Any value can be read as its text representation
+    fn from_sql(_ty: &SnowflakeType, raw: &str) -> Result<Self, FromSqlError> {
+        Ok(raw.to_owned())
+    }
+}
+
+impl FromSql for bool {
+    fn accepts(ty: &SnowflakeType) -> bool {
+        matches!(ty, SnowflakeType::Boolean)
+    }
+
+    fn from_sql(ty: &SnowflakeType, raw: &str) -> Result<Self, FromSqlError> {
+        if raw == "1" || raw.eq_ignore_ascii_case("true") {
+            Ok(true)
+        } else if raw == "0" || raw.eq_ignore_ascii_case("false") {
+            Ok(false)
+        } else {
+            Err(conversion_error::<Self>(*ty, raw))
+        }
+    }
+}
+
+/// Binary values are returned hex-encoded
+impl FromSql for Vec<u8> {
+    fn accepts(ty: &SnowflakeType) -> bool {
+        matches!(ty, SnowflakeType::Binary)
+    }
+
+    fn from_sql(ty: &SnowflakeType, raw: &str) -> Result<Self, FromSqlError> {
+        if !raw.len().is_multiple_of(2) || !raw.is_ascii() {
+            return Err(conversion_error::<Self>(*ty, raw));
+        }
+
+        (0..raw.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&raw[i..i + 2], 16))
+            .collect::<Result<_, _>>()
+            .map_err(|_| conversion_error::<Self>(*ty, raw))
+    }
+}
+
+impl<T: FromSql> FromSql for Option<T> {
+    fn accepts(ty: &SnowflakeType) -> bool {
+        T::accepts(ty)
+    }
+
+    fn from_sql(ty: &SnowflakeType, raw: &str) -> Result<Self, FromSqlError> {
+        T::from_sql(ty, raw).map(Some)
+    }
+
+    fn from_sql_null(_ty: &SnowflakeType) -> Result<Self, FromSqlError> {
+        Ok(None)
+    }
+
+    fn from_sql_field(field: &FieldSchema, raw: Option<&str>) -> Result<Self, FromSqlError> {
+        raw.map(|raw| T::from_sql_field(field, Some(raw)))
+            .transpose()
+    }
+}
+
+/// Scale of the column, if it is a valid `NUMBER` scale.
+/// Snowflake doesn't support negative scales, they are ignored rather than rounding values.
+#[cfg(any(feature = "rust_decimal", feature = "bigdecimal"))]
+fn column_scale(field: &FieldSchema) -> Option<u32> {
+    field
+        .scale
+        .filter(|_| field.type_ == SnowflakeType::Fixed)
+        .and_then(|scale| u32::try_from(scale).ok())
+}
+
+#[cfg(feature = "rust_decimal")]
+mod rust_decimal_impls {
+    use super::{column_scale, conversion_error, FromSql, FromSqlError};
+    use crate::responses::SnowflakeType;
+    use crate::FieldSchema;
+
+    /// Decimals are parsed exactly from their text, without going through floats.
+    /// When read from a column they are padded to its scale, eg `1.5` of `NUMBER(10, 2)` is `1.50`.
+    ///
+    /// ```rust
+    /// use rust_decimal::Decimal;
+    /// use snowflake_api::from_sql::FromSql;
+    /// use snowflake_api::{FieldSchema, SnowflakeType};
+    ///
+    /// let column = |scale| FieldSchema {
+    ///     name: "AMOUNT".to_owned(),
+    ///     type_: SnowflakeType::Fixed,
+    ///     scale: Some(scale),
+    ///     precision: Some(38),
+    ///     length: None,
+    ///     nullable: true,
+    /// };
+    ///
+    /// let value = Decimal::from_sql_field(&column(0), Some("42")).unwrap();
+    /// assert_eq!(value.to_string(), "42");
+    ///
+    /// let value = Decimal::from_sql_field(&column(2), Some("1.5")).unwrap();
+    /// assert_eq!(value.to_string(), "1.50");
+    ///
+    /// let raw = "1234567890.123456789012345678";
+    /// let value = Decimal::from_sql_field(&column(18), Some(raw)).unwrap();
+    /// assert_eq!(value.to_string(), raw);
+    ///
+    /// // negative scales aren't valid for Snowflake and are ignored
+    /// let value = Decimal::from_sql_field(&column(-2), Some("1200")).unwrap();
+    /// assert_eq!(value.to_string(), "1200");
+    ///
+    /// let value = Option::<Decimal>::from_sql_field(&column(2), None).unwrap();
+    /// assert_eq!(value, None);
+    /// ```
+    impl FromSql for rust_decimal::Decimal {
+        fn accepts(ty: &SnowflakeType) -> bool {
+            matches!(ty, SnowflakeType::Fixed)
+        }
+
+        fn from_sql(ty: &SnowflakeType, raw: &str) -> Result<Self, FromSqlError> {
+            raw.parse::<Self>()
+                .or_else(|_| Self::from_scientific(raw))
+                .map_err(|_| conversion_error::<Self>(*ty, raw))
+        }
+
+        fn from_sql_field(field: &FieldSchema, raw: Option<&str>) -> Result<Self, FromSqlError> {
+            let mut value = Self::from_sql_nullable(&field.type_, raw)?;
+            // only pad, digits beyond the scale of the column are never dropped
+            if let Some(scale) = column_scale(field).filter(|scale| value.scale() < *scale) {
+                value.rescale(scale);
+            }
+            Ok(value)
+        }
+    }
+}
+
+#[cfg(feature = "bigdecimal")]
+mod bigdecimal_impls {
+    use super::{column_scale, conversion_error, FromSql, FromSqlError};
+    use crate::responses::SnowflakeType;
+    use crate::FieldSchema;
+
+    /// Same as for `rust_decimal::Decimal`, values are padded to the scale of the column
+    ///
+    /// ```rust
+    /// use bigdecimal::BigDecimal;
+    /// use snowflake_api::from_sql::FromSql;
+    /// use snowflake_api::{FieldSchema, SnowflakeType};
+    ///
+    /// let column = FieldSchema {
+    ///     name: "AMOUNT".to_owned(),
+    ///     type_: SnowflakeType::Fixed,
+    ///     scale: Some(3),
+    ///     precision: Some(38),
+    ///     length: None,
+    ///     nullable: false,
+    /// };
+    /// let value = BigDecimal::from_sql_field(&column, Some("-0.5")).unwrap();
+    /// assert_eq!(value.to_string(), "-0.500");
+    /// ```
+    impl FromSql for bigdecimal::BigDecimal {
+        fn accepts(ty: &SnowflakeType) -> bool {
+            matches!(ty, SnowflakeType::Fixed)
+        }
+
+        fn from_sql(ty: &SnowflakeType, raw: &str) -> Result<Self, FromSqlError> {
+            raw.parse().map_err(|_| conversion_error::<Self>(*ty, raw))
+        }
+
+        fn from_sql_field(field: &FieldSchema, raw: Option<&str>) -> Result<Self, FromSqlError> {
+            let value = Self::from_sql_nullable(&field.type_, raw)?;
+            match column_scale(field).map(i64::from) {
+                Some(scale) if value.fractional_digit_count() < scale => {
+                    Ok(value.with_scale(scale))
+                }
+                _ => Ok(value),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "chrono")]
+mod chrono_impls {
+    use chrono::{DateTime, FixedOffset};
+
+    use super::{conversion_error, FromSql, FromSqlError};
+    use crate::responses::SnowflakeType;
+
+    /// `TIMESTAMP_TZ` values are returned as epoch seconds with a fraction and the offset
+    /// in minutes shifted by 1440, eg `1706745600.123456789 1200` is `-04:00`.
+    /// Text in the `YYYY-MM-DD HH24:MI:SS.FF TZH:TZM` format, as it is bound, is read as well.
+    ///
+    /// ```rust
+    /// use chrono::{DateTime, FixedOffset, TimeZone, Utc};
+    /// use snowflake_api::from_sql::FromSql;
+    /// use snowflake_api::SnowflakeType;
+    ///
+    /// let ty = SnowflakeType::TimestampTz;
+    /// let value = DateTime::<FixedOffset>::from_sql(&ty, "1706745600.123456789 1200").unwrap();
+    /// assert_eq!(value.offset(), &FixedOffset::west_opt(4 * 3600).unwrap());
+    /// assert_eq!(value.timestamp(), 1_706_745_600);
+    /// assert_eq!(value.timestamp_subsec_nanos(), 123_456_789);
+    /// assert_eq!(value.to_rfc3339(), "2024-01-31T20:00:00.123456789-04:00");
+    ///
+    /// let value = DateTime::<FixedOffset>::from_sql(&ty, "-1.5 1770").unwrap();
+    /// assert_eq!(value.to_rfc3339(), "1970-01-01T05:29:58.500+05:30");
+    /// assert_eq!(
+    ///     value,
+    ///     Utc.with_ymd_and_hms(1969, 12, 31, 23, 59, 58).unwrap()
+    ///         + chrono::Duration::milliseconds(500)
+    /// );
+    ///
+    /// let value = DateTime::<FixedOffset>::from_sql(&ty, "2024-01-31 12:00:00.000000000 +00:00");
+    /// assert_eq!(value.unwrap().timestamp(), 1_706_702_400);
+    /// ```
+    impl FromSql for DateTime<FixedOffset> {
+        fn accepts(ty: &SnowflakeType) -> bool {
+            matches!(ty, SnowflakeType::TimestampTz)
+        }
+
+        fn from_sql(ty: &SnowflakeType, raw: &str) -> Result<Self, FromSqlError> {
+            parse_epoch_with_offset(raw)
+                .or_else(|| DateTime::parse_from_str(raw, "%Y-%m-%d %H:%M:%S%.f %:z").ok())
+                .ok_or_else(|| conversion_error::<Self>(*ty, raw))
+        }
+    }
+
+    fn parse_epoch_with_offset(raw: &str) -> Option<DateTime<FixedOffset>> {
+        let (epoch, offset) = raw.trim().split_once(' ')?;
+        let offset = FixedOffset::east_opt((offset.parse::<i32>().ok()? - 1440) * 60)?;
+
+        let (secs, fraction) = epoch.split_once('.').unwrap_or((epoch, ""));
+        if fraction.len() > 9 || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let mut secs = secs.parse::<i64>().ok()?;
+        let mut nanos = format!("{fraction:0<9}").parse::<u32>().ok()?;
+        // the fraction carries the sign of the whole value, eg `-1.5` is 0.5s after `-2`
+        if epoch.starts_with('-') && nanos > 0 {
+            secs -= 1;
+            nanos = 1_000_000_000 - nanos;
+        }
+
+        Some(DateTime::from_timestamp(secs, nanos)?.with_timezone(&offset))
+    }
+}
@@ -0,0 +1,114 @@
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use flate2::read::GzDecoder;
+use futures::stream::{self, StreamExt, TryStreamExt};
+use object_store::aws::AmazonS3;
+use object_store::ObjectStore;
+use tokio::io::AsyncWriteExt;
+use tokio::task;
+
+use crate::put::s3_store;
+use crate::responses::{PutGetExecResponse, PutGetStageInfo};
+use crate::SnowflakeApiError;
+
+/// Options of the file download, see [`crate::SnowflakeApi::get_file`]
+#[derive(Debug, Clone, Default)]
+pub struct GetOptions {
+    /// Decompress downloaded `.gz` files, dropping the extension from their names
+    pub decompress: bool,
+}
+
+pub async fn get(resp: PutGetExecResponse, options: &GetOptions) -> Result<(), SnowflakeApiError> {
+    let local_dir = resp
+        .data
+        .local_location
+        .as_deref()
+        .map(local_dir)
+        .ok_or(SnowflakeApiError::UnexpectedResponse)?;
+
+    match resp.data.stage_info {
+        PutGetStageInfo::Aws(info) => {
+            let (s3, bucket_path) = s3_store(info)?;
+            get_from_s3(
+                &s3,
+                &bucket_path,
+                resp.data.src_locations,
+                &local_dir,
+                resp.data.parallel,
+                options,
+            )
+            .await
+        }
+        PutGetStageInfo::Azure(_) => Err(SnowflakeApiError::Unimplemented(
+            "GET stage file requests for Azure".to_string(),
+        )),
+        PutGetStageInfo::Gcs(_) => Err(SnowflakeApiError::Unimplemented(
+            "GET stage file requests for GCS".to_string(),
+        )),
+    }
+}
+
+/// Local location is returned as given in the command, eg `file:///tmp/data/`
+fn local_dir(location: &str) -> PathBuf {
+    PathBuf::from(location.strip_prefix("file://").unwrap_or(location))
+}
+
+/// Files are downloaded into the local directory under their own names,
+/// even if they are nested inside the stage
+async fn get_from_s3(
+    s3: &AmazonS3,
+    bucket_path: &str,
+    src_locations: Vec<String>,
+    local_dir: &Path,
+    max_parallel_downloads: usize,
+    options: &GetOptions,
+) -> Result<(), SnowflakeApiError> {
+    tokio::fs::create_dir_all(local_dir).await?;
+
+    stream::iter(src_locations)
+        .map(|src| async move {
+            let filename = Path::new(&src)
+                .file_name()
+                .ok_or_else(|| SnowflakeApiError::InvalidLocalPath(src.clone()))?;
+            let dest = local_dir.join(filename);
+            download_file(s3, &format!("{bucket_path}{src}"), &dest).await?;
+
+            if options.decompress && dest.extension().is_some_and(|e| e == "gz") {
+                task::spawn_blocking(move || gunzip(&dest)).await??;
+            }
+            Ok::<(), SnowflakeApiError>(())
+        })
+        .buffer_unordered(max_parallel_downloads.max(1))
+        .try_collect()
+        .await
+}
+
+async fn download_file(
+    store: &AmazonS3,
+    src_path: &str,
+    dest: &Path,
+) -> Result<(), SnowflakeApiError> {
+    log::debug!("Downloading {src_path} into {}", dest.display());
+    let src_path = object_store::path::Path::parse(src_path)?;
+    let mut chunks = store.get(&src_path).await?.into_stream();
+
+    let mut file = tokio::fs::File::create(dest).await?;
+    while let Some(chunk) = chunks.next().await {
+        file.write_all(&chunk?).await?;
+    }
+    file.flush().await?;
+
+    Ok(())
+}
+
+/// Replaces `name.gz` with decompressed `name`
+fn gunzip(path: &Path) -> Result<(), SnowflakeApiError> {
+    let dest = path.with_extension("");
+    let mut decoder = GzDecoder::new(File::open(path)?);
+    io::copy(&mut decoder, &mut File::create(&dest)?)?;
+    std::fs::remove_file(path)?;
+
+    Ok(())
+}
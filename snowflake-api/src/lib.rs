@@ -10,12 +10,15 @@ clippy::missing_errors_doc,
 clippy::module_name_repetitions,
 clippy::struct_field_names,
 clippy::future_not_send, // This one seems like something we should eventually fix
-clippy::missing_panics_doc
+clippy::missing_panics_doc,
+clippy::duration_suboptimal_units, // `Duration::from_mins` is too recent for the crate's users
 )]
 
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::{Display, Formatter};
 use std::io;
 use std::sync::Arc;
+use std::time::Duration;
 
 use arrow::error::ArrowError;
 use arrow::ipc::reader::StreamReader;
@@ -23,26 +26,65 @@ use arrow::record_batch::RecordBatch;
 use base64::Engine;
 use bytes::{Buf, Bytes};
 use futures::future::try_join_all;
+use futures::stream::{self, BoxStream, StreamExt, TryStreamExt};
 use regex::Regex;
 use reqwest_middleware::ClientWithMiddleware;
+use serde::de::DeserializeOwned;
 use thiserror::Error;
+use uuid::Uuid;
 
-use responses::ExecResponse;
-use session::{AuthError, Session};
+use responses::{
+    AbortQueryResponse, CommandType, ExecErrorResponse, ExecResponse, QueryExecResponse,
+    QueryExecResponseData, QueryResultFormat, QueryStatusEntry, QueryStatusResponse,
+};
+use session::{AuthError, Session, SESSION_EXPIRED_CODE};
 
 use crate::connection::QueryType;
-use crate::connection::{Connection, ConnectionError};
-use crate::requests::ExecRequest;
-use crate::responses::{ExecResponseRowType, SnowflakeType};
+use crate::connection::{Connection, ConnectionError, RetryConfig};
+use crate::de::RenameRule;
+use crate::pool::SessionPool;
+use crate::requests::{AbortRequest, ExecRequest};
+use crate::responses::{ExecResponseChunk, ExecResponseRowType};
 use crate::session::AuthError::MissingEnvArgument;
 
+pub use crate::bindings::{quote_identifier, quote_literal};
+pub use crate::get::GetOptions;
+pub use crate::load::{FileLoadResult, LoadResult};
+pub use crate::prepared::PreparedStatement;
+pub use crate::put::PutOptions;
+pub use crate::requests::{BindingValue, Bindings, ExecRequestBuilder, ParameterBinding};
+pub use crate::responses::{QueryStatus, SnowflakeType, UnknownSnowflakeType};
+pub use crate::row::Row;
+pub use crate::transport::Transport;
+
+pub mod arrow_types;
+pub mod bindings;
 pub mod connection;
+pub mod de;
+mod dsn;
+mod external_browser;
+pub mod from_sql;
+mod get;
+mod load;
+mod okta;
 #[cfg(feature = "polars")]
 mod polars;
+pub mod pool;
+mod prepared;
 mod put;
 mod requests;
 mod responses;
+pub mod row;
 mod session;
+mod trace;
+pub mod transport;
+
+/// Bounds of the backoff between query status checks
+const POLL_INTERVAL_MIN: Duration = Duration::from_millis(500);
+const POLL_INTERVAL_MAX: Duration = Duration::from_secs(5);
+/// Consecutive `NO_DATA` statuses after which the query is considered unknown,
+/// a freshly submitted query is reported with it only until it becomes visible
+const MAX_NO_DATA_POLLS: u32 = 24;
 
 #[derive(Error, Debug)]
 pub enum SnowflakeApiError {
@@ -58,6 +100,9 @@ pub enum SnowflakeApiError {
     #[error(transparent)]
     ArrowError(#[from] arrow::error::ArrowError),
 
+    #[error(transparent)]
+    BindingError(#[from] bindings::BindingError),
+
     #[error("S3 bucket path in PUT request is invalid: `{0}`")]
     InvalidBucketPath(String),
 
@@ -79,6 +124,15 @@ pub enum SnowflakeApiError {
     #[error("Snowflake API error. Code: `{0}`. Message: `{1}`")]
     ApiError(String, String),
 
+    /// Statement failed on the server, eg `002003` for a missing or unauthorized object
+    #[error("Snowflake error. Code: `{code}`. SQL state: `{}`. Message: `{message}`", sql_state.as_deref().unwrap_or_default())]
+    SnowflakeError {
+        code: String,
+        sql_state: Option<String>,
+        message: String,
+        query_id: Option<String>,
+    },
+
     #[error("Snowflake API empty response could mean that query wasn't executed correctly or API call was faulty")]
     EmptyResponse,
 
@@ -91,11 +145,112 @@ pub enum SnowflakeApiError {
     #[error("Unexpected API response")]
     UnexpectedResponse,
 
+    #[error("Query didn't complete within {0:?}")]
+    Timeout(Duration),
+
+    #[error("Query `{0}` finished with {1:?} status. Code: `{2}`. Message: `{3}`")]
+    QueryFailed(String, QueryStatus, String, String),
+
+    /// No status was reported for the query, its id is likely wrong
+    #[error("No information was returned about query `{0}`, it might not exist")]
+    QueryNotFound(String),
+
     #[error(transparent)]
     GlobPatternError(#[from] glob::PatternError),
 
     #[error(transparent)]
     GlobError(#[from] glob::GlobError),
+
+    #[error(transparent)]
+    UnknownColumnType(#[from] UnknownSnowflakeType),
+
+    #[error("Failed to deserialize result row: {0}")]
+    RowDeserializationError(#[from] serde_json::Error),
+
+    #[error("Invalid connection string: {0}")]
+    InvalidDsn(String),
+
+    #[error("No statements to execute")]
+    NoStatements,
+}
+
+/// Error code of queries cancelled by `STATEMENT_TIMEOUT_IN_SECONDS`
+const STATEMENT_TIMEOUT_CODE: &str = "000630";
+
+impl From<ExecErrorResponse> for SnowflakeApiError {
+    fn from(resp: ExecErrorResponse) -> Self {
+        SnowflakeApiError::SnowflakeError {
+            code: resp.data.error_code,
+            sql_state: resp.data.sql_state,
+            message: resp.message.unwrap_or_default(),
+            query_id: resp.data.query_id,
+        }
+    }
+}
+
+/// Record batches of the result, as they are downloaded, see [`SnowflakeApi::exec_stream`]
+pub type RecordBatchStream = BoxStream<'static, Result<RecordBatch, SnowflakeApiError>>;
+
+/// Limits of the result fetching, see [`SnowflakeApi::exec_stream_with_options`]
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct FetchOptions {
+    /// Stop after this many rows, the last batch is truncated.
+    /// Chunks past the limit are not downloaded, and downloads in flight are dropped with the stream.
+    pub max_rows: Option<usize>,
+    /// Chunks downloaded concurrently ahead of the one being read, `1` downloads them one at a time
+    pub prefetch: usize,
+}
+
+impl Default for FetchOptions {
+    fn default() -> Self {
+        Self {
+            max_rows: None,
+            prefetch: 1,
+        }
+    }
+}
+
+impl FetchOptions {
+    pub fn max_rows(mut self, max_rows: usize) -> Self {
+        self.max_rows = Some(max_rows);
+        self
+    }
+
+    pub fn prefetch(mut self, prefetch: usize) -> Self {
+        self.prefetch = prefetch;
+        self
+    }
+}
+
+/// Number of leading chunks holding at least `max_rows` rows, rows of the inline rowset aren't known upfront
+fn chunks_for_rows(chunks: &[ExecResponseChunk], max_rows: usize) -> usize {
+    let mut rows = 0;
+    chunks
+        .iter()
+        .position(|chunk| {
+            rows += usize::try_from(chunk.row_count).unwrap_or_default();
+            rows >= max_rows
+        })
+        .map_or(chunks.len(), |last| last + 1)
+}
+
+/// Ends the stream once `max_rows` rows are read, without polling the batches past the limit
+fn limit_rows(batches: RecordBatchStream, max_rows: usize) -> RecordBatchStream {
+    stream::unfold(Some((batches, max_rows)), |state| async move {
+        let (mut batches, remaining) = state?;
+        match batches.next().await? {
+            Ok(batch) if batch.num_rows() >= remaining => {
+                Some((Ok(batch.slice(0, remaining)), None))
+            }
+            Ok(batch) => {
+                let remaining = remaining - batch.num_rows();
+                Some((Ok(batch), Some((batches, remaining))))
+            }
+            Err(e) => Some((Err(e), Some((batches, remaining)))),
+        }
+    })
+    .boxed()
 }
 
 /// Even if Arrow is specified as a return type non-select queries
@@ -114,15 +269,21 @@ impl Display for JsonResult {
 }
 
 /// Based on the [`ExecResponseRowType`]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct FieldSchema {
     pub name: String,
     // todo: is it a good idea to expose internal response struct to the user?
     pub type_: SnowflakeType,
     pub scale: Option<i64>,
     pub precision: Option<i64>,
+    /// Maximum number of characters of `TEXT` columns
+    pub length: Option<i64>,
     pub nullable: bool,
 }
 
+/// Metadata of a result column, see [`QueryResult::columns`]
+pub type ColumnMetadata = FieldSchema;
+
 impl From<ExecResponseRowType> for FieldSchema {
     fn from(value: ExecResponseRowType) -> Self {
         FieldSchema {
@@ -130,11 +291,82 @@ impl From<ExecResponseRowType> for FieldSchema {
             type_: value.type_,
             scale: value.scale,
             precision: value.precision,
+            length: value.length,
             nullable: value.nullable,
         }
     }
 }
 
+impl TryFrom<&arrow::datatypes::Field> for FieldSchema {
+    type Error = UnknownSnowflakeType;
+
+    /// Arrow results describe columns with the same attributes in field metadata
+    fn try_from(field: &arrow::datatypes::Field) -> Result<Self, Self::Error> {
+        let metadata = field.metadata();
+        let number = |key: &str| metadata.get(key).and_then(|v| v.parse().ok());
+
+        Ok(FieldSchema {
+            name: field.name().clone(),
+            type_: metadata
+                .get("logicalType")
+                .map_or("", String::as_str)
+                .parse()?,
+            scale: number("scale"),
+            precision: number("precision"),
+            length: number("charLength"),
+            nullable: field.is_nullable(),
+        })
+    }
+}
+
+/// Result of an executed statement with the ids it can be found by,
+/// eg in `QUERY_HISTORY` or for support tickets, see [`SnowflakeApi::exec_query`]
+pub struct ExecutedQuery {
+    /// `queryId` assigned by Snowflake
+    pub query_id: String,
+    /// `requestId` the statement was submitted with, see [`SnowflakeApi::cancel_query`]
+    pub request_id: Uuid,
+    pub result: QueryResult,
+}
+
+/// Size of a query result, known before its chunks are downloaded, see [`SnowflakeApi::get_result_metadata`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResultMetadata {
+    pub query_id: String,
+    /// Rows in the whole result
+    pub total: i64,
+    /// Rows returned inline with the response, the rest is in `chunks`
+    pub returned: i64,
+    pub chunks: Vec<ChunkMetadata>,
+}
+
+/// Part of the result stored separately, downloaded from its presigned URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkMetadata {
+    pub url: String,
+    pub row_count: i32,
+    pub uncompressed_size: i64,
+}
+
+impl ResultMetadata {
+    fn from_response(data: &QueryExecResponseData) -> Self {
+        Self {
+            query_id: data.query_id.clone(),
+            total: data.total,
+            returned: data.returned,
+            chunks: data
+                .chunks
+                .iter()
+                .map(|c| ChunkMetadata {
+                    url: c.url.clone(),
+                    row_count: c.row_count,
+                    uncompressed_size: c.uncompressed_size,
+                })
+                .collect(),
+        }
+    }
+}
+
 /// Container for query result.
 /// Arrow is returned by-default for all SELECT statements,
 /// unless there is session configuration issue or it's a different statement type.
@@ -144,6 +376,69 @@ pub enum QueryResult {
     Empty,
 }
 
+impl QueryResult {
+    /// Columns of the result, as described by Snowflake
+    ///
+    /// ```rust
+    /// use std::collections::HashMap;
+    /// use std::sync::Arc;
+    ///
+    /// use arrow::array::{Int64Array, StringArray};
+    /// use arrow::datatypes::{DataType, Field, Schema};
+    /// use arrow::record_batch::RecordBatch;
+    /// use snowflake_api::{QueryResult, SnowflakeType};
+    ///
+    /// let metadata = |pairs: &[(&str, &str)]| {
+    ///     pairs
+    ///         .iter()
+    ///         .map(|(k, v)| (k.to_string(), v.to_string()))
+    ///         .collect::<HashMap<_, _>>()
+    /// };
+    /// let schema = Schema::new(vec![
+    ///     Field::new("PRICE", DataType::Int64, false).with_metadata(metadata(&[
+    ///         ("logicalType", "FIXED"),
+    ///         ("precision", "10"),
+    ///         ("scale", "2"),
+    ///     ])),
+    ///     Field::new("NAME", DataType::Utf8, true)
+    ///         .with_metadata(metadata(&[("logicalType", "TEXT"), ("charLength", "16")])),
+    /// ]);
+    /// let batch = RecordBatch::try_new(
+    ///     Arc::new(schema),
+    ///     vec![
+    ///         Arc::new(Int64Array::from(vec![150])),
+    ///         Arc::new(StringArray::from(vec!["apple"])),
+    ///     ],
+    /// )
+    /// .unwrap();
+    ///
+    /// let columns = QueryResult::Arrow(vec![batch]).columns().unwrap();
+    /// assert_eq!(columns[0].name, "PRICE");
+    /// assert_eq!(columns[0].type_, SnowflakeType::Fixed);
+    /// assert_eq!((columns[0].precision, columns[0].scale), (Some(10), Some(2)));
+    /// assert!(!columns[0].nullable);
+    /// assert_eq!(columns[1].type_, SnowflakeType::Text);
+    /// assert_eq!(columns[1].length, Some(16));
+    /// assert!(columns[1].nullable);
+    ///
+    /// assert!(QueryResult::Empty.columns().unwrap().is_empty());
+    /// ```
+    pub fn columns(&self) -> Result<Vec<ColumnMetadata>, UnknownSnowflakeType> {
+        match self {
+            QueryResult::Arrow(batches) => batches.first().map_or(Ok(Vec::new()), |batch| {
+                batch
+                    .schema()
+                    .fields()
+                    .iter()
+                    .map(|field| FieldSchema::try_from(field.as_ref()))
+                    .collect()
+            }),
+            QueryResult::Json(json) => Ok(json.schema.clone()),
+            QueryResult::Empty => Ok(Vec::new()),
+        }
+    }
+}
+
 /// Raw query result
 /// Can be transformed into [`QueryResult`]
 pub enum RawQueryResult {
@@ -194,13 +489,37 @@ pub struct AuthArgs {
 
 impl AuthArgs {
     pub fn from_env() -> Result<AuthArgs, SnowflakeApiError> {
-        let auth_type = if let Ok(password) = std::env::var("SNOWFLAKE_PASSWORD") {
-            Ok(AuthType::Password(PasswordArgs { password }))
+        let okta_url = std::env::var("SNOWFLAKE_AUTHENTICATOR")
+            .ok()
+            .filter(|a| a.starts_with("https://"));
+        let auth_type = if let (Some(authenticator_url), Ok(password)) =
+            (okta_url, std::env::var("SNOWFLAKE_PASSWORD"))
+        {
+            Ok(AuthType::Okta(OktaArgs {
+                authenticator_url,
+                password,
+            }))
+        } else if let Ok(password) = std::env::var("SNOWFLAKE_PASSWORD") {
+            Ok(AuthType::Password(PasswordArgs {
+                password,
+                passcode: std::env::var("SNOWFLAKE_PASSCODE").ok(),
+            }))
         } else if let Ok(private_key_pem) = std::env::var("SNOWFLAKE_PRIVATE_KEY") {
             Ok(AuthType::Certificate(CertificateArgs { private_key_pem }))
+        } else if let Ok(token) = std::env::var("SNOWFLAKE_OAUTH_TOKEN") {
+            Ok(AuthType::OAuth(OAuthArgs { token }))
+        } else if let Ok(token) = std::env::var("SNOWFLAKE_PAT") {
+            Ok(AuthType::ProgrammaticAccessToken(
+                ProgrammaticAccessTokenArgs { token },
+            ))
+        } else if std::env::var("SNOWFLAKE_AUTHENTICATOR")
+            .is_ok_and(|a| a.eq_ignore_ascii_case("externalbrowser"))
+        {
+            Ok(AuthType::ExternalBrowser(ExternalBrowserArgs::default()))
         } else {
             Err(MissingEnvArgument(
-                "SNOWFLAKE_PASSWORD or SNOWFLAKE_PRIVATE_KEY".to_owned(),
+                "SNOWFLAKE_PASSWORD, SNOWFLAKE_PRIVATE_KEY, SNOWFLAKE_OAUTH_TOKEN, SNOWFLAKE_PAT or SNOWFLAKE_AUTHENTICATOR"
+                    .to_owned(),
             ))
         };
 
@@ -216,80 +535,325 @@ impl AuthArgs {
             auth_type: auth_type?,
         })
     }
+
+    /// Password authentication of `user` to `ACCOUNT` with the password `password`,
+    /// for clients sending requests to a [`MockTransport`](crate::transport::MockTransport)
+    #[cfg(any(test, feature = "test-util"))]
+    pub fn for_tests() -> Self {
+        AuthArgs {
+            account_identifier: "ACCOUNT".to_owned(),
+            warehouse: None,
+            database: None,
+            schema: None,
+            username: "user".to_owned(),
+            role: None,
+            auth_type: AuthType::Password(PasswordArgs {
+                password: "password".to_owned(),
+                passcode: None,
+            }),
+        }
+    }
 }
 
+#[derive(Clone)]
 pub enum AuthType {
     Password(PasswordArgs),
     Certificate(CertificateArgs),
+    OAuth(OAuthArgs),
+    ProgrammaticAccessToken(ProgrammaticAccessTokenArgs),
+    ExternalBrowser(ExternalBrowserArgs),
+    Okta(OktaArgs),
 }
 
+#[derive(Clone)]
 pub struct PasswordArgs {
     pub password: String,
+    /// MFA passcode, required for accounts enrolled in DUO unless push notifications are used
+    pub passcode: Option<String>,
 }
 
+/// Key-pair authentication with the RSA private key in PKCS#8 PEM format, requires the
+/// `cert-auth` feature. The login carries a JWT signed with the key, see
+/// `snowflake_jwt::generate_jwt_token`.
+#[derive(Clone)]
 pub struct CertificateArgs {
     pub private_key_pem: String,
 }
 
+#[derive(Clone)]
+pub struct OAuthArgs {
+    pub token: String,
+}
+
+/// Programmatic access token generated for the user, eg with `ALTER USER ... ADD PROGRAMMATIC ACCESS TOKEN`.
+/// It replaces the password, but is sent to the login endpoint as a token
+/// with the `PROGRAMMATIC_ACCESS_TOKEN` authenticator.
+#[derive(Clone)]
+pub struct ProgrammaticAccessTokenArgs {
+    pub token: String,
+}
+
+/// Interactive SSO: the identity provider login page is opened in the default browser,
+/// which then redirects to a listener on `localhost` to hand the token over
+#[derive(Debug, Clone)]
+pub struct ExternalBrowserArgs {
+    /// Port of the redirect listener, `0` picks any free port
+    pub redirect_port: u16,
+    /// How long to wait for the login in the browser to complete
+    pub timeout: Duration,
+}
+
+/// Native Okta SSO without a browser, for organizations using Okta as the identity provider.
+/// The user's Okta password is checked by Okta, Snowflake only gets the SAML response issued for it.
+/// Okta MFA isn't supported by this flow, use [`AuthType::ExternalBrowser`] for it.
+///
+/// The SSO and token URLs must belong to the authenticator, and the SAML response must be posted
+/// back to the account being logged into, otherwise the login fails before anything is sent to them.
+#[derive(Clone)]
+pub struct OktaArgs {
+    /// Okta URL of the organization, eg `https://myorg.okta.com`
+    pub authenticator_url: String,
+    pub password: String,
+}
+
+impl Default for ExternalBrowserArgs {
+    fn default() -> Self {
+        Self {
+            redirect_port: 0,
+            timeout: Duration::from_secs(120),
+        }
+    }
+}
+
+/// Client metadata sent on login, Snowflake shows it in session details and query history
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientInfo {
+    /// Name of the application using the driver, `CLIENT_ENVIRONMENT.APPLICATION`
+    pub application: String,
+    /// Driver name, Snowflake only accepts names of the drivers it knows about
+    pub client_app_id: String,
+    /// Driver version, old versions may be rejected for a known driver name
+    pub client_app_version: String,
+}
+
+impl Default for ClientInfo {
+    fn default() -> Self {
+        Self {
+            application: "Rust".to_owned(),
+            client_app_id: "Go".to_owned(),
+            client_app_version: "1.6.22".to_owned(),
+        }
+    }
+}
+
+/// How certificate revocation checks are handled, reported to Snowflake on login
+///
+/// Other drivers query OCSP responders while connecting, and their mode decides whether
+/// unreachable responders fail the connection. This client relies on the TLS stack of `reqwest`
+/// for certificate validation and doesn't query OCSP responders itself, so blocked OCSP endpoints
+/// don't prevent connecting in any mode. The mode is only sent along with the client environment,
+/// eg to match the setting of other drivers used in the same organization.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OcspMode {
+    /// Connect when the revocation status can't be fetched, rejecting revoked certificates
+    #[default]
+    FailOpen,
+    /// Only connect when the certificate is confirmed not to be revoked
+    FailClosed,
+    /// Skip revocation checks
+    Insecure,
+}
+
+impl OcspMode {
+    /// Name sent to Snowflake, eg `FAIL_OPEN`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::FailOpen => "FAIL_OPEN",
+            Self::FailClosed => "FAIL_CLOSED",
+            Self::Insecure => "INSECURE",
+        }
+    }
+}
+
 #[must_use]
 pub struct SnowflakeApiBuilder {
     pub auth: AuthArgs,
-    client: Option<ClientWithMiddleware>,
+    transport: Option<Arc<dyn Transport>>,
+    retry: RetryConfig,
+    client_info: ClientInfo,
+    ocsp_mode: OcspMode,
+    session_parameters: BTreeMap<String, serde_json::Value>,
+    compression_threshold: Option<usize>,
 }
 
 impl SnowflakeApiBuilder {
     pub fn new(auth: AuthArgs) -> Self {
-        Self { auth, client: None }
+        Self {
+            auth,
+            transport: None,
+            retry: RetryConfig::default(),
+            client_info: ClientInfo::default(),
+            ocsp_mode: OcspMode::default(),
+            session_parameters: BTreeMap::new(),
+            compression_threshold: None,
+        }
     }
 
+    /// Send requests through the given client, eg with extra middleware.
+    /// Retries are done according to [`SnowflakeApiBuilder::with_retry_config`],
+    /// the client must not add a retry middleware of its own, attempts would multiply.
     pub fn with_client(mut self, client: ClientWithMiddleware) -> Self {
-        self.client = Some(client);
+        self.transport = Some(Arc::new(client));
+        self
+    }
+
+    /// Send requests through a custom [`Transport`], eg `MockTransport` of the `test-util` feature.
+    /// Replaces the client given with [`SnowflakeApiBuilder::with_client`].
+    pub fn with_transport(mut self, transport: impl Transport + 'static) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Retry configuration for transient HTTP and Snowflake failures, see [`RetryConfig`].
+    /// Applies to requests sent through any client or transport, eg [`SnowflakeApiBuilder::with_transport`].
+    pub fn with_retry_config(mut self, retry: RetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Gzip bodies of query requests larger than `threshold` bytes, see [`Connection::with_request_compression`].
+    /// Useful with large bindings, eg arrays or `VARIANT` values, requests are sent uncompressed by default.
+    pub fn with_request_compression(mut self, threshold: usize) -> Self {
+        self.compression_threshold = Some(threshold);
+        self
+    }
+
+    /// Client metadata sent on login, see [`ClientInfo`].
+    /// Usually only the application name needs to be changed,
+    /// eg `ClientInfo { application: "my-etl".to_owned(), ..ClientInfo::default() }`.
+    pub fn with_client_info(mut self, client_info: ClientInfo) -> Self {
+        self.client_info = client_info;
+        self
+    }
+
+    /// OCSP mode sent on login, see [`OcspMode`], `FAIL_OPEN` by default
+    pub fn with_ocsp_mode(mut self, ocsp_mode: OcspMode) -> Self {
+        self.ocsp_mode = ocsp_mode;
+        self
+    }
+
+    /// Session parameter set on login, eg `TIMEZONE` or `TIMESTAMP_OUTPUT_FORMAT`,
+    /// which saves an `ALTER SESSION` round-trip.
+    /// Names are case-insensitive, setting the same parameter again replaces its value.
+    pub fn with_session_parameter(
+        mut self,
+        name: impl Into<String>,
+        value: impl Into<serde_json::Value>,
+    ) -> Self {
+        self.session_parameters
+            .insert(name.into().to_uppercase(), value.into());
         self
     }
 
     pub fn build(self) -> Result<SnowflakeApi, SnowflakeApiError> {
-        let connection = match self.client {
-            Some(client) => Arc::new(Connection::new_with_middware(client)),
-            None => Arc::new(Connection::new()?),
-        };
+        let connection = self.connection()?;
+        Ok(self.api(&connection))
+    }
 
-        let session = match self.auth.auth_type {
-            AuthType::Password(args) => Session::password_auth(
-                Arc::clone(&connection),
-                &self.auth.account_identifier,
-                self.auth.warehouse.as_deref(),
-                self.auth.database.as_deref(),
-                self.auth.schema.as_deref(),
-                &self.auth.username,
-                self.auth.role.as_deref(),
-                &args.password,
-            ),
-            AuthType::Certificate(args) => Session::cert_auth(
-                Arc::clone(&connection),
-                &self.auth.account_identifier,
-                self.auth.warehouse.as_deref(),
-                self.auth.database.as_deref(),
-                self.auth.schema.as_deref(),
-                &self.auth.username,
-                self.auth.role.as_deref(),
-                &args.private_key_pem,
-            ),
+    /// Build a pool of `size` sessions sharing the same connection, see [`SessionPool`]
+    pub fn build_pool(self, size: usize) -> Result<SessionPool, SnowflakeApiError> {
+        let connection = self.connection()?;
+        let sessions = (0..size).map(|_| self.api(&connection)).collect();
+        Ok(SessionPool::new(sessions))
+    }
+
+    fn connection(&self) -> Result<Arc<Connection>, SnowflakeApiError> {
+        let connection = match &self.transport {
+            Some(transport) => Connection::with_transport(Arc::clone(transport)),
+            None => Connection::new()?,
+        }
+        .with_retry_config(self.retry.clone());
+        let connection = match self.compression_threshold {
+            Some(threshold) => connection.with_request_compression(threshold),
+            None => connection,
         };
+        Ok(Arc::new(connection))
+    }
+
+    fn api(&self, connection: &Arc<Connection>) -> SnowflakeApi {
+        let session = Session::new(
+            Arc::clone(connection),
+            &self.auth.account_identifier,
+            self.auth.warehouse.as_deref(),
+            self.auth.database.as_deref(),
+            self.auth.schema.as_deref(),
+            &self.auth.username,
+            self.auth.role.as_deref(),
+            self.auth.auth_type.clone(),
+        )
+        .with_client_info(self.client_info.clone())
+        .with_ocsp_mode(self.ocsp_mode);
+        let session = self
+            .session_parameters
+            .iter()
+            .fold(session, |session, (name, value)| {
+                session.with_session_parameter(name, value.clone())
+            });
 
         let account_identifier = self.auth.account_identifier.to_uppercase();
 
-        Ok(SnowflakeApi::new(
-            Arc::clone(&connection),
-            session,
-            account_identifier,
-        ))
+        SnowflakeApi::new(Arc::clone(connection), session, account_identifier)
     }
 }
 
-/// Snowflake API, keeps connection pool and manages session for you
+/// Object name as it is written in `USE` statements.
+/// Names which are valid unquoted identifiers are resolved by Snowflake in upper case,
+/// they are upper-cased and left unquoted. Any other name is taken literally, see [`quote_identifier`].
+fn object_name(name: &str) -> String {
+    if is_unquoted_identifier(name) {
+        name.to_uppercase()
+    } else {
+        quote_identifier(name)
+    }
+}
+
+fn is_unquoted_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+/// Name as it is resolved by Snowflake, which is how login parameters expect it
+fn context_name(name: &str) -> String {
+    if is_unquoted_identifier(name) {
+        name.to_uppercase()
+    } else {
+        name.to_owned()
+    }
+}
+
+/// Background heartbeat of the session, see [`SnowflakeApi::start_heartbeat`].
+/// Heartbeats stop when the handle is dropped.
+pub struct HeartbeatHandle {
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl Drop for HeartbeatHandle {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Snowflake API, keeps connection pool and manages session for you.
+///
+/// Queries can be run concurrently from several tasks sharing the same instance,
+/// each statement gets its own sequence id within the session.
 pub struct SnowflakeApi {
     connection: Arc<Connection>,
-    session: Session,
+    // shared with the heartbeat task
+    session: Arc<Session>,
     account_identifier: String,
 }
 
@@ -298,23 +862,24 @@ impl SnowflakeApi {
     pub fn new(connection: Arc<Connection>, session: Session, account_identifier: String) -> Self {
         Self {
             connection,
-            session,
+            session: Arc::new(session),
             account_identifier,
         }
     }
-    /// Initialize object with password auth. Authentication happens on the first request.
-    pub fn with_password_auth(
+
+    /// Object with a new connection, authenticated on the first request
+    fn with_auth_type(
         account_identifier: &str,
         warehouse: Option<&str>,
         database: Option<&str>,
         schema: Option<&str>,
         username: &str,
         role: Option<&str>,
-        password: &str,
+        auth_type: AuthType,
     ) -> Result<Self, SnowflakeApiError> {
         let connection = Arc::new(Connection::new()?);
 
-        let session = Session::password_auth(
+        let session = Session::new(
             Arc::clone(&connection),
             account_identifier,
             warehouse,
@@ -322,18 +887,38 @@ impl SnowflakeApi {
             schema,
             username,
             role,
-            password,
+            auth_type,
         );
 
         let account_identifier = account_identifier.to_uppercase();
-        Ok(Self::new(
-            Arc::clone(&connection),
-            session,
+        Ok(Self::new(connection, session, account_identifier))
+    }
+
+    /// Initialize object with password auth. Authentication happens on the first request.
+    pub fn with_password_auth(
+        account_identifier: &str,
+        warehouse: Option<&str>,
+        database: Option<&str>,
+        schema: Option<&str>,
+        username: &str,
+        role: Option<&str>,
+        password: &str,
+    ) -> Result<Self, SnowflakeApiError> {
+        Self::with_auth_type(
             account_identifier,
-        ))
+            warehouse,
+            database,
+            schema,
+            username,
+            role,
+            AuthType::Password(PasswordArgs {
+                password: password.to_owned(),
+                passcode: None,
+            }),
+        )
     }
 
-    /// Initialize object with private certificate auth. Authentication happens on the first request.
+    /// Initialize object with private certificate (key-pair) auth. Authentication happens on the first request.
     pub fn with_certificate_auth(
         account_identifier: &str,
         warehouse: Option<&str>,
@@ -343,31 +928,117 @@ impl SnowflakeApi {
         role: Option<&str>,
         private_key_pem: &str,
     ) -> Result<Self, SnowflakeApiError> {
-        let connection = Arc::new(Connection::new()?);
+        Self::with_auth_type(
+            account_identifier,
+            warehouse,
+            database,
+            schema,
+            username,
+            role,
+            AuthType::Certificate(CertificateArgs {
+                private_key_pem: private_key_pem.to_owned(),
+            }),
+        )
+    }
 
-        let session = Session::cert_auth(
-            Arc::clone(&connection),
+    /// Initialize object with OAuth access token auth. Authentication happens on the first request.
+    pub fn with_oauth_auth(
+        account_identifier: &str,
+        warehouse: Option<&str>,
+        database: Option<&str>,
+        schema: Option<&str>,
+        username: &str,
+        role: Option<&str>,
+        token: &str,
+    ) -> Result<Self, SnowflakeApiError> {
+        Self::with_auth_type(
             account_identifier,
             warehouse,
             database,
             schema,
             username,
             role,
-            private_key_pem,
-        );
+            AuthType::OAuth(OAuthArgs {
+                token: token.to_owned(),
+            }),
+        )
+    }
 
-        let account_identifier = account_identifier.to_uppercase();
-        Ok(Self::new(
-            Arc::clone(&connection),
-            session,
+    /// Initialize object with programmatic access token auth. Authentication happens on the first request.
+    pub fn with_programmatic_access_token_auth(
+        account_identifier: &str,
+        warehouse: Option<&str>,
+        database: Option<&str>,
+        schema: Option<&str>,
+        username: &str,
+        role: Option<&str>,
+        token: &str,
+    ) -> Result<Self, SnowflakeApiError> {
+        Self::with_auth_type(
             account_identifier,
-        ))
+            warehouse,
+            database,
+            schema,
+            username,
+            role,
+            AuthType::ProgrammaticAccessToken(ProgrammaticAccessTokenArgs {
+                token: token.to_owned(),
+            }),
+        )
+    }
+
+    /// Initialize object with external browser (SSO) auth. Authentication happens on the first request,
+    /// which waits for the user to log in.
+    pub fn with_external_browser_auth(
+        account_identifier: &str,
+        warehouse: Option<&str>,
+        database: Option<&str>,
+        schema: Option<&str>,
+        username: &str,
+        role: Option<&str>,
+        args: ExternalBrowserArgs,
+    ) -> Result<Self, SnowflakeApiError> {
+        Self::with_auth_type(
+            account_identifier,
+            warehouse,
+            database,
+            schema,
+            username,
+            role,
+            AuthType::ExternalBrowser(args),
+        )
+    }
+
+    /// Initialize object with native Okta auth, see [`OktaArgs`]
+    pub fn with_okta_auth(
+        account_identifier: &str,
+        warehouse: Option<&str>,
+        database: Option<&str>,
+        schema: Option<&str>,
+        username: &str,
+        role: Option<&str>,
+        args: OktaArgs,
+    ) -> Result<Self, SnowflakeApiError> {
+        Self::with_auth_type(
+            account_identifier,
+            warehouse,
+            database,
+            schema,
+            username,
+            role,
+            AuthType::Okta(args),
+        )
     }
 
     pub fn from_env() -> Result<Self, SnowflakeApiError> {
         SnowflakeApiBuilder::new(AuthArgs::from_env()?).build()
     }
 
+    /// Connect with a connection string, see [`AuthArgs::from_dsn`]
+    pub fn from_dsn(dsn: &str) -> Result<Self, SnowflakeApiError> {
+        SnowflakeApiBuilder::new(AuthArgs::from_dsn(dsn)?).build()
+    }
+
     /// Closes the current session, this is necessary to clean up temporary objects (tables, functions, etc)
     /// which are Snowflake session dependent.
     /// If another request is made the new session will be initiated.
@@ -376,8 +1047,88 @@ impl SnowflakeApi {
         Ok(())
     }
 
+    /// Exchange the session token for a new one ahead of its expiration.
+    /// Session tokens are renewed automatically, this is only needed to extend long idle sessions.
+    pub async fn renew_session(&self) -> Result<(), SnowflakeApiError> {
+        self.session.renew_session().await?;
+        Ok(())
+    }
+
+    /// Switch the warehouse of the session.
+    /// Names which are valid unquoted identifiers, eg `compute_wh`, are resolved in upper case
+    /// as in SQL, any other name is taken literally, see [`quote_identifier`].
+    ///
+    /// `USE` statements of this and other `use_*` methods are sent as internal,
+    /// see [`ExecRequestBuilder::internal`]. Heartbeats have their own endpoint and aren't statements at all.
+    pub async fn use_warehouse(&self, name: &str) -> Result<(), SnowflakeApiError> {
+        self.use_object("WAREHOUSE", name).await?;
+        self.session
+            .update_context(|c| c.warehouse = Some(context_name(name)));
+        Ok(())
+    }
+
+    /// Switch the role of the session, the name is treated as in [`SnowflakeApi::use_warehouse`]
+    pub async fn use_role(&self, name: &str) -> Result<(), SnowflakeApiError> {
+        self.use_object("ROLE", name).await?;
+        self.session
+            .update_context(|c| c.role = Some(context_name(name)));
+        Ok(())
+    }
+
+    /// Switch the database of the session, the schema is reset to `PUBLIC` by Snowflake
+    pub async fn use_database(&self, name: &str) -> Result<(), SnowflakeApiError> {
+        self.use_object("DATABASE", name).await?;
+        self.session.update_context(|c| {
+            c.database = Some(context_name(name));
+            c.schema = None;
+        });
+        Ok(())
+    }
+
+    /// Switch the schema of the session within the current database
+    pub async fn use_schema(&self, name: &str) -> Result<(), SnowflakeApiError> {
+        self.use_object("SCHEMA", name).await?;
+        self.session
+            .update_context(|c| c.schema = Some(context_name(name)));
+        Ok(())
+    }
+
+    async fn use_object(&self, kind: &str, name: &str) -> Result<(), SnowflakeApiError> {
+        self.exec_internal(&format!("USE {kind} {}", object_name(name)))
+            .await?;
+        Ok(())
+    }
+
+    /// Statement issued by the client on its own, see [`ExecRequestBuilder::internal`]
+    async fn exec_internal(&self, sql: &str) -> Result<QueryResult, SnowflakeApiError> {
+        self.exec_request(ExecRequestBuilder::new().sql(sql).internal(true))
+            .await
+    }
+
+    /// Start sending heartbeats for the session every `interval`, so that it doesn't expire while idle.
+    /// Heartbeats are only sent once the session is started by the first request.
+    /// The task runs until the returned handle is dropped, failed heartbeats are logged and skipped.
+    pub fn start_heartbeat(&self, interval: Duration) -> HeartbeatHandle {
+        let session = Arc::clone(&self.session);
+        let task = tokio::spawn(async move {
+            let mut ticks =
+                tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+            ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticks.tick().await;
+                if let Err(e) = session.heartbeat().await {
+                    log::warn!("Session heartbeat failed: {e}");
+                }
+            }
+        });
+        HeartbeatHandle { task }
+    }
+
     /// Execute a single query against API.
     /// If statement is PUT, then file will be uploaded to the Snowflake-managed storage
+    ///
+    /// Rows are decoded according to the `queryResultFormat` of the response,
+    /// [`QueryResult::Json`] for `json` and [`QueryResult::Arrow`] for `arrow`.
     pub async fn exec(&self, sql: &str) -> Result<QueryResult, SnowflakeApiError> {
         let raw = self.exec_raw(sql).await?;
         let res = raw.deserialize_arrow()?;
@@ -388,126 +1139,1745 @@ impl SnowflakeApi {
     /// If statement is PUT, then file will be uploaded to the Snowflake-managed storage
     /// Returns raw bytes in the Arrow response
     pub async fn exec_raw(&self, sql: &str) -> Result<RawQueryResult, SnowflakeApiError> {
-        let put_re = Regex::new(r"(?i)^(?:/\*.*\*/\s*)*put\s+").unwrap();
+        self.exec_raw_with_request_id(sql, Uuid::new_v4()).await
+    }
 
-        // put commands go through a different flow and result is side-effect
-        if put_re.is_match(sql) {
-            log::info!("Detected PUT query");
-            self.exec_put(sql).await.map(|()| RawQueryResult::Empty)
-        } else {
-            self.exec_arrow_raw(sql).await
+    /// Execute a single query, submitted with the given `request_id`.
+    /// Running query can be aborted from another task with [`SnowflakeApi::cancel_query`].
+    ///
+    /// Retries keep the `request_id`, eg when the statement is resubmitted after renewing
+    /// an expired session token, so Snowflake doesn't execute non-idempotent statements twice.
+    pub async fn exec_with_request_id(
+        &self,
+        sql: &str,
+        request_id: Uuid,
+    ) -> Result<QueryResult, SnowflakeApiError> {
+        let raw = self.exec_raw_with_request_id(sql, request_id).await?;
+        let res = raw.deserialize_arrow()?;
+        Ok(res)
+    }
+
+    /// Execute a single query and return its result as Arrow record batches,
+    /// with Snowflake encodings converted into logical Arrow types, see [`arrow_types::to_logical_types`].
+    /// Statements without a result set return no batches.
+    ///
+    /// The result is requested in Arrow format with the `QUERY_RESULT_FORMAT` statement parameter,
+    /// so it doesn't depend on the format set for the session.
+    pub async fn query_arrow(&self, sql: &str) -> Result<Vec<RecordBatch>, SnowflakeApiError> {
+        let request = ExecRequestBuilder::new()
+            .sql(sql)
+            .parameter("QUERY_RESULT_FORMAT", "ARROW");
+        match self.exec_request(request).await? {
+            QueryResult::Arrow(batches) => Ok(batches
+                .iter()
+                .map(arrow_types::to_logical_types)
+                .collect::<Result<_, _>>()?),
+            QueryResult::Json(_) => Err(SnowflakeApiError::Unimplemented(
+                "conversion of JSON results into Arrow".to_owned(),
+            )),
+            QueryResult::Empty => Ok(Vec::new()),
+        }
+    }
+
+    /// Execute a query built with [`ExecRequestBuilder`], eg with parameter bindings.
+    /// Asynchronous requests return as soon as the query is submitted, with [`QueryResult::Empty`],
+    /// use [`SnowflakeApi::exec_query_request`] or [`SnowflakeApi::execute_async`] to get the id of such query.
+    pub async fn exec_request(
+        &self,
+        request: ExecRequestBuilder,
+    ) -> Result<QueryResult, SnowflakeApiError> {
+        Ok(self.exec_query_request(request).await?.result)
+    }
+
+    /// Execute a single query and return its result along with the query and request ids.
+    /// PUT and GET statements are not supported, see [`SnowflakeApi::put_file`] and [`SnowflakeApi::get_file`].
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "test-util")]
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), snowflake_api::SnowflakeApiError> {
+    /// use serde_json::json;
+    /// use snowflake_api::transport::MockTransport;
+    /// use snowflake_api::{AuthArgs, SnowflakeApiBuilder};
+    ///
+    /// let mock = MockTransport::new();
+    /// # mock.push_login();
+    /// mock.push_query_result(json!([]), json!([]));
+    /// # let auth = AuthArgs::for_tests();
+    /// let api = SnowflakeApiBuilder::new(auth)
+    ///     .with_transport(mock.clone())
+    ///     .build()?;
+    ///
+    /// let query = api.exec_query("DELETE FROM ORDERS WHERE ID = 0").await?;
+    /// assert_eq!(query.query_id, "01b2c3d4");
+    ///
+    /// let sent = &mock.requests()[1];
+    /// let request_id = query.request_id.to_string();
+    /// assert!(sent.url.query_pairs().any(|(k, v)| k == "requestId" && v == request_id));
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "test-util"))]
+    /// # fn main() {}
+    /// ```
+    pub async fn exec_query(&self, sql: &str) -> Result<ExecutedQuery, SnowflakeApiError> {
+        self.exec_query_request(ExecRequestBuilder::new().sql(sql))
+            .await
+    }
+
+    /// Same as [`SnowflakeApi::exec_request`], returning the query and request ids along with the result.
+    /// Asynchronous requests return the id of the submitted query with [`QueryResult::Empty`].
+    pub async fn exec_query_request(
+        &self,
+        request: ExecRequestBuilder,
+    ) -> Result<ExecutedQuery, SnowflakeApiError> {
+        let request_id = Uuid::new_v4();
+        let Some(timeout) = request.client_timeout() else {
+            return self.exec_built_request(request, request_id).await;
+        };
+
+        let result =
+            tokio::time::timeout(timeout, self.exec_built_request(request, request_id)).await;
+        match result {
+            Ok(Err(SnowflakeApiError::SnowflakeError { code, .. }))
+                if code == STATEMENT_TIMEOUT_CODE =>
+            {
+                return Err(SnowflakeApiError::Timeout(timeout));
+            }
+            Ok(result) => return result,
+            Err(_) => {}
+        }
+
+        // don't leave the query running on the server
+        if let Err(e) = self.cancel_query(request_id).await {
+            log::warn!("Failed to cancel timed out request {request_id}: {e}");
+        }
+        Err(SnowflakeApiError::Timeout(timeout))
+    }
+
+    async fn exec_built_request(
+        &self,
+        request: ExecRequestBuilder,
+        request_id: Uuid,
+    ) -> Result<ExecutedQuery, SnowflakeApiError> {
+        let body = request.build();
+        if body.async_exec {
+            let resp = self
+                .run_query::<ExecResponse>(body, QueryType::ArrowQuery, request_id)
+                .await?;
+            return Ok(ExecutedQuery {
+                query_id: Self::submitted_query_id(resp)?,
+                request_id,
+                result: QueryResult::Empty,
+            });
         }
+
+        let resp = self.exec_query_response(body, request_id).await?;
+        let query_id = resp.data.query_id.clone();
+        let raw = self.raw_result(resp).await?;
+        Ok(ExecutedQuery {
+            query_id,
+            request_id,
+            result: raw.deserialize_arrow()?,
+        })
+    }
+
+    /// Execute a query and deserialize its rows into `T` with serde, matching columns by name.
+    /// Column names are lower-cased, use [`SnowflakeApi::query_as_with`] to match them differently.
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "test-util")]
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), snowflake_api::SnowflakeApiError> {
+    /// use serde::Deserialize;
+    /// # use serde_json::json;
+    /// # use snowflake_api::transport::MockTransport;
+    /// # use snowflake_api::{AuthArgs, SnowflakeApiBuilder};
+    ///
+    /// # let mock = MockTransport::new();
+    /// # mock.push_login();
+    /// # mock.push_query_result(
+    /// #     json!([
+    /// #         { "name": "ID", "type": "fixed", "scale": 0, "precision": 18, "nullable": false },
+    /// #         { "name": "FULL_NAME", "type": "text", "length": 64, "nullable": true }
+    /// #     ]),
+    /// #     json!([["1", "Ada Lovelace"], ["2", "Alan Turing"]]),
+    /// # );
+    /// # let auth = AuthArgs::for_tests();
+    /// # let api = SnowflakeApiBuilder::new(auth).with_transport(mock).build()?;
+    /// #[derive(Deserialize)]
+    /// struct User {
+    ///     id: u64,
+    ///     #[serde(rename = "full_name")]
+    ///     name: String,
+    /// }
+    ///
+    /// let users: Vec<User> = api.query_as("SELECT ID, FULL_NAME FROM USERS").await?;
+    /// assert_eq!(users.len(), 2);
+    /// assert_eq!(users[1].id, 2);
+    /// assert_eq!(users[1].name, "Alan Turing");
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "test-util"))]
+    /// # fn main() {}
+    /// ```
+    pub async fn query_as<T: DeserializeOwned>(
+        &self,
+        sql: &str,
+    ) -> Result<Vec<T>, SnowflakeApiError> {
+        self.query_as_with(sql, RenameRule::default()).await
+    }
+
+    /// Same as [`SnowflakeApi::query_as`], with the given rule of matching column names
+    pub async fn query_as_with<T: DeserializeOwned>(
+        &self,
+        sql: &str,
+        rename: RenameRule,
+    ) -> Result<Vec<T>, SnowflakeApiError> {
+        self.exec(sql).await?.deserialize_rows(rename)
+    }
+
+    /// Statement to execute repeatedly with different bindings, see [`PreparedStatement`]
+    pub fn prepare(&self, sql: &str) -> PreparedStatement<'_> {
+        PreparedStatement::new(self, sql)
     }
 
-    async fn exec_put(&self, sql: &str) -> Result<(), SnowflakeApiError> {
+    /// Insert rows with a single request, see [`ExecRequestBuilder::insert_rows`].
+    /// Nothing is sent if there are no rows.
+    pub async fn insert_rows<'a, R: AsRef<[&'a dyn bindings::ToSql]>>(
+        &self,
+        table: &str,
+        columns: &[&str],
+        rows: &[R],
+    ) -> Result<QueryResult, SnowflakeApiError> {
+        if rows.is_empty() {
+            return Ok(QueryResult::Empty);
+        }
+        let request = ExecRequestBuilder::insert_rows(table, columns, rows)?;
+        self.exec_request(request).await
+    }
+
+    /// Execute several statements in a single request, results are returned in the same order.
+    /// Statements are executed sequentially, the first failing one aborts the rest.
+    ///
+    /// Statements are joined with `;` and Snowflake checks their count against `statements.len()`,
+    /// so a single entry must not contain several statements separated by `;` itself.
+    /// An empty slice is rejected with [`SnowflakeApiError::NoStatements`].
+    pub async fn execute_multi(
+        &self,
+        statements: &[&str],
+    ) -> Result<Vec<QueryResult>, SnowflakeApiError> {
+        // a count of 0 lets the server run any number of statements
+        if statements.is_empty() {
+            return Err(SnowflakeApiError::NoStatements);
+        }
+        let sql = statements
+            .iter()
+            .map(|s| s.trim().trim_end_matches(';'))
+            .collect::<Vec<_>>()
+            .join(";\n");
+        let body = ExecRequest {
+            parameters: Some(HashMap::from([(
+                "MULTI_STATEMENT_COUNT".to_owned(),
+                serde_json::Value::from(statements.len()),
+            )])),
+            ..ExecRequest::new(&sql)
+        };
+
+        let resp = self.exec_query_response(body, Uuid::new_v4()).await?;
+
+        // results of the child statements are referenced by their query ids
+        let Some(result_ids) = resp.data.result_ids.clone().filter(|ids| !ids.is_empty()) else {
+            let raw = self.raw_result(resp).await?;
+            return Ok(vec![raw.deserialize_arrow()?]);
+        };
+
+        let mut results = Vec::with_capacity(statements.len());
+        for query_id in result_ids.split(',') {
+            let resp = self.fetch_query_result(query_id).await?;
+            let raw = self.raw_result(Self::query_response(resp)?).await?;
+            results.push(raw.deserialize_arrow()?);
+        }
+        Ok(results)
+    }
+
+    /// Execute a single query and stream its result as Arrow record batches.
+    /// Result chunks are downloaded one at a time as the stream is polled,
+    /// so only the chunk being read is kept in memory.
+    /// Chunk download and decoding errors are returned as stream items.
+    ///
+    /// PUT statements and JSON results are not supported.
+    pub async fn exec_stream(&self, sql: &str) -> Result<RecordBatchStream, SnowflakeApiError> {
+        self.exec_stream_with_options(sql, &FetchOptions::default())
+            .await
+    }
+
+    /// Same as [`SnowflakeApi::exec_stream`], with a row limit and chunk prefetch, see [`FetchOptions`].
+    /// Prefetched chunks are still returned in the order of the result.
+    pub async fn exec_stream_with_options(
+        &self,
+        sql: &str,
+        options: &FetchOptions,
+    ) -> Result<RecordBatchStream, SnowflakeApiError> {
         let resp = self
-            .run_sql::<ExecResponse>(sql, QueryType::JsonQuery)
+            .exec_query_response(ExecRequest::new(sql), Uuid::new_v4())
             .await?;
-        log::debug!("Got PUT response: {:?}", resp);
 
+        if resp.data.returned == 0 || options.max_rows == Some(0) {
+            log::debug!("Got response with 0 rows");
+            return Ok(stream::empty().boxed());
+        } else if Self::is_json_result(&resp.data)? {
+            return Err(SnowflakeApiError::Unimplemented(
+                "streaming of JSON results".to_owned(),
+            ));
+        }
+        let Some(base64) = resp.data.rowset_base64 else {
+            return Err(SnowflakeApiError::BrokenResponse);
+        };
+
+        // inline rowset is the first chunk of the result
+        let first: Option<Result<Bytes, SnowflakeApiError>> = if base64.is_empty() {
+            None
+        } else {
+            let bytes = base64::engine::general_purpose::STANDARD.decode(base64)?;
+            Some(Ok(Bytes::from(bytes)))
+        };
+
+        let mut chunks = resp.data.chunks;
+        if let Some(max_rows) = options.max_rows {
+            chunks.truncate(chunks_for_rows(&chunks, max_rows));
+        }
+
+        let connection = Arc::clone(&self.connection);
+        let headers = Arc::new(resp.data.chunk_headers);
+        let chunks = stream::iter(chunks)
+            .map(move |chunk| {
+                let connection = Arc::clone(&connection);
+                let headers = Arc::clone(&headers);
+                async move { Ok(connection.get_chunk(&chunk.url, &headers).await?) }
+            })
+            .buffered(options.prefetch.max(1));
+
+        let batches = stream::iter(first)
+            .chain(chunks)
+            .and_then(|bytes| async move { Ok(RawQueryResult::bytes_to_batches(bytes)?) })
+            .map_ok(|batches| stream::iter(batches.into_iter().map(Ok)))
+            .try_flatten()
+            .boxed();
+
+        Ok(match options.max_rows {
+            Some(max_rows) => limit_rows(batches, max_rows),
+            None => batches,
+        })
+    }
+
+    /// Upload local file, or files matching the glob pattern, to the stage.
+    /// `stage_path` is the stage name with an optional path, eg `@my_stage/data/`.
+    pub async fn put_file(
+        &self,
+        local_path: &str,
+        stage_path: &str,
+        options: &PutOptions,
+    ) -> Result<(), SnowflakeApiError> {
+        let stage_path = if stage_path.starts_with('@') {
+            stage_path.to_owned()
+        } else {
+            format!("@{stage_path}")
+        };
+        let sql = format!(
+            "PUT 'file://{}' {stage_path} AUTO_COMPRESS={} OVERWRITE={}",
+            local_path.replace('\\', "\\\\").replace('\'', "\\'"),
+            options.auto_compress.to_string().to_uppercase(),
+            options.overwrite.to_string().to_uppercase(),
+        );
+
+        self.exec_transfer(&sql, Uuid::new_v4(), &GetOptions::default())
+            .await
+    }
+
+    /// Bulk load local files into the table: the files are uploaded into a temporary stage
+    /// with [`SnowflakeApi::put_file`] and copied with `COPY INTO`, then the stage is dropped.
+    ///
+    /// `local_files` is a path or a glob pattern, as for `put_file`. `file_format` are the options of
+    /// the `FILE_FORMAT` clause, eg `TYPE = CSV SKIP_HEADER = 1` or `FORMAT_NAME = my_format`.
+    /// The table name is used as-is, quote it if needed.
+    /// `COPY INTO` runs with its default `ON_ERROR = ABORT_STATEMENT`, so a rejected record fails
+    /// the whole load with [`SnowflakeApiError::SnowflakeError`],
+    /// see [`SnowflakeApi::load_table_with_options`] to skip rejected records instead.
+    pub async fn load_table(
+        &self,
+        table: &str,
+        local_files: &str,
+        file_format: &str,
+    ) -> Result<LoadResult, SnowflakeApiError> {
+        self.load_table_with_options(table, local_files, file_format, "")
+            .await
+    }
+
+    /// Same as [`SnowflakeApi::load_table`], with the copy options of `COPY INTO`,
+    /// eg `ON_ERROR = CONTINUE` to load the valid records and report the rejected ones
+    /// in [`LoadResult::errors_seen`] and [`LoadResult::failed_files`].
+    pub async fn load_table_with_options(
+        &self,
+        table: &str,
+        local_files: &str,
+        file_format: &str,
+        copy_options: &str,
+    ) -> Result<LoadResult, SnowflakeApiError> {
+        let stage = format!("SNOWFLAKE_RS_LOAD_{}", Uuid::new_v4().simple());
+        self.exec_internal(&format!("CREATE TEMPORARY STAGE {stage}"))
+            .await?;
+
+        let loaded = async {
+            self.put_file(local_files, &stage, &PutOptions::default())
+                .await?;
+            let mut sql = format!("COPY INTO {table} FROM @{stage} FILE_FORMAT = ({file_format})");
+            if !copy_options.is_empty() {
+                sql = format!("{sql} {copy_options}");
+            }
+            let result = self.exec(&sql).await?;
+            load::load_result(result)
+        }
+        .await;
+
+        // temporary stages are dropped with the session anyway, failing to drop one isn't fatal
+        if let Err(e) = self
+            .exec_internal(&format!("DROP STAGE IF EXISTS {stage}"))
+            .await
+        {
+            log::warn!("Failed to drop stage {stage}: {e}");
+        }
+        loaded
+    }
+
+    /// Download files matching the stage path into the local directory, keeping their names.
+    /// `stage_path` is the stage name with an optional path or file name prefix, eg `@my_stage/data/`.
+    pub async fn get_file(
+        &self,
+        stage_path: &str,
+        local_dir: &str,
+        options: &GetOptions,
+    ) -> Result<(), SnowflakeApiError> {
+        let stage_path = if stage_path.starts_with('@') {
+            stage_path.to_owned()
+        } else {
+            format!("@{stage_path}")
+        };
+        let local_dir = if local_dir.ends_with('/') {
+            local_dir.to_owned()
+        } else {
+            format!("{local_dir}/")
+        };
+        let sql = format!(
+            "GET {stage_path} 'file://{}'",
+            local_dir.replace('\\', "\\\\").replace('\'', "\\'"),
+        );
+
+        self.exec_transfer(&sql, Uuid::new_v4(), options).await
+    }
+
+    /// Aborts the query which was submitted with the given `request_id`
+    pub async fn cancel_query(&self, request_id: Uuid) -> Result<(), SnowflakeApiError> {
+        self.abort_request(request_id, "").await
+    }
+
+    async fn abort_request(
+        &self,
+        request_id: Uuid,
+        sql_text: &str,
+    ) -> Result<(), SnowflakeApiError> {
+        log::debug!("Cancelling request {request_id}");
+        let parts = self.session.get_token().await?;
+
+        let body = AbortRequest {
+            sql_text: sql_text.to_owned(),
+            request_id: request_id.to_string(),
+        };
+
+        let resp = self
+            .connection
+            .request::<AbortQueryResponse>(
+                QueryType::AbortQuery,
+                &self.account_identifier,
+                &[],
+                Some(&parts.session_token_auth_header),
+                body,
+            )
+            .await?;
+
+        if resp.success {
+            Ok(())
+        } else {
+            Err(SnowflakeApiError::ApiError(
+                resp.code.unwrap_or_default(),
+                resp.message.unwrap_or_default(),
+            ))
+        }
+    }
+
+    async fn exec_raw_with_request_id(
+        &self,
+        sql: &str,
+        request_id: Uuid,
+    ) -> Result<RawQueryResult, SnowflakeApiError> {
+        let transfer_re = Regex::new(r"(?i)^(?:/\*.*\*/\s*)*(?:put|get)\s+").unwrap();
+
+        // put and get commands go through a different flow and result is side-effect
+        if transfer_re.is_match(sql) {
+            log::info!("Detected PUT or GET query");
+            self.exec_transfer(sql, request_id, &GetOptions::default())
+                .await
+                .map(|()| RawQueryResult::Empty)
+        } else {
+            self.exec_arrow_raw(sql, request_id).await
+        }
+    }
+
+    async fn exec_transfer(
+        &self,
+        sql: &str,
+        request_id: Uuid,
+        get_options: &GetOptions,
+    ) -> Result<(), SnowflakeApiError> {
+        let resp = self
+            .run_query::<ExecResponse>(ExecRequest::new(sql), QueryType::JsonQuery, request_id)
+            .await?;
         match resp {
-            ExecResponse::Query(_) => Err(SnowflakeApiError::UnexpectedResponse),
-            ExecResponse::PutGet(pg) => put::put(pg).await,
-            ExecResponse::Error(e) => Err(SnowflakeApiError::ApiError(
-                e.data.error_code,
-                e.message.unwrap_or_default(),
-            )),
+            ExecResponse::Query(_) | ExecResponse::Async(_) => {
+                Err(SnowflakeApiError::UnexpectedResponse)
+            }
+            ExecResponse::PutGet(pg) => {
+                // the stage credentials of the response are not logged
+                log::debug!("Got {:?} response", pg.data.command);
+                match pg.data.command {
+                    CommandType::Upload => put::put(pg).await,
+                    CommandType::Download => get::get(pg, get_options).await,
+                }
+            }
+            ExecResponse::Error(e) => Err(e.into()),
         }
     }
 
     /// Useful for debugging to get the straight query response
     #[cfg(debug_assertions)]
     pub async fn exec_response(&mut self, sql: &str) -> Result<ExecResponse, SnowflakeApiError> {
-        self.run_sql::<ExecResponse>(sql, QueryType::ArrowQuery)
+        self.run_query::<ExecResponse>(ExecRequest::new(sql), QueryType::ArrowQuery, Uuid::new_v4())
             .await
     }
 
     /// Useful for debugging to get raw JSON response
     #[cfg(debug_assertions)]
     pub async fn exec_json(&mut self, sql: &str) -> Result<serde_json::Value, SnowflakeApiError> {
-        self.run_sql::<serde_json::Value>(sql, QueryType::JsonQuery)
-            .await
+        self.run_query::<serde_json::Value>(
+            ExecRequest::new(sql),
+            QueryType::JsonQuery,
+            Uuid::new_v4(),
+        )
+        .await
+    }
+
+    /// Submits a query for asynchronous execution and returns its id without waiting for a result.
+    /// Use [`SnowflakeApi::get_query_status`] and [`SnowflakeApi::get_query_result`] to follow up.
+    pub async fn execute_async(&self, sql: &str) -> Result<String, SnowflakeApiError> {
+        let resp = self
+            .run_query::<ExecResponse>(
+                ExecRequest {
+                    async_exec: true,
+                    ..ExecRequest::new(sql)
+                },
+                QueryType::ArrowQuery,
+                Uuid::new_v4(),
+            )
+            .await?;
+        log::debug!("Got async query response: {resp:?}");
+
+        Self::submitted_query_id(resp)
+    }
+
+    fn submitted_query_id(resp: ExecResponse) -> Result<String, SnowflakeApiError> {
+        match resp {
+            ExecResponse::Async(ar) => Ok(ar.data.query_id),
+            // query could finish before the response was sent
+            ExecResponse::Query(qr) => Ok(qr.data.query_id),
+            ExecResponse::PutGet(_) => Err(SnowflakeApiError::UnexpectedResponse),
+            ExecResponse::Error(e) => Err(e.into()),
+        }
     }
 
-    async fn exec_arrow_raw(&self, sql: &str) -> Result<RawQueryResult, SnowflakeApiError> {
+    /// Current execution status of the query with given id
+    pub async fn get_query_status(&self, query_id: &str) -> Result<QueryStatus, SnowflakeApiError> {
+        Ok(self.query_status_entry(query_id).await?.status)
+    }
+
+    /// Waits for the query with given id to finish and returns its result.
+    /// Failed or aborted queries are returned as [`SnowflakeApiError::QueryFailed`].
+    ///
+    /// The status is polled with a backoff from half a second up to 5 seconds between requests,
+    /// polling stops at the first failed status and the result is not fetched.
+    ///
+    /// Unknown query ids are reported with `NO_DATA`, polling stops after 24 of them in a row:
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "test-util")]
+    /// # #[tokio::main(flavor = "current_thread", start_paused = true)]
+    /// # async fn main() -> Result<(), snowflake_api::SnowflakeApiError> {
+    /// # use snowflake_api::transport::MockTransport;
+    /// use serde_json::json;
+    /// use snowflake_api::{AuthArgs, SnowflakeApiBuilder, SnowflakeApiError};
+    ///
+    /// let mock = MockTransport::new();
+    /// # mock.push_login();
+    /// for _ in 0..24 {
+    ///     mock.push_json(
+    ///         "/monitoring/queries/bogus",
+    ///         &json!({
+    ///             "code": null, "message": null, "success": true,
+    ///             "data": { "queries": [{ "id": "bogus", "status": "NO_DATA" }] }
+    ///         }),
+    ///     );
+    /// }
+    /// # let auth = AuthArgs::for_tests();
+    /// let api = SnowflakeApiBuilder::new(auth)
+    ///     .with_transport(mock.clone())
+    ///     .build()?;
+    ///
+    /// let result = api.get_query_result("bogus").await;
+    /// assert!(matches!(result, Err(SnowflakeApiError::QueryNotFound(id)) if id == "bogus"));
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "test-util"))]
+    /// # fn main() {}
+    /// ```
+    pub async fn get_query_result(&self, query_id: &str) -> Result<QueryResult, SnowflakeApiError> {
+        let resp = self.wait_query_result(query_id, None).await?;
+        let raw = self.raw_result(Self::query_response(resp)?).await?;
+        Ok(raw.deserialize_arrow()?)
+    }
+
+    /// Same as [`SnowflakeApi::get_query_result`], failing with [`SnowflakeApiError::Timeout`]
+    /// if the query doesn't finish in time. The query itself keeps running.
+    pub async fn get_query_result_with_timeout(
+        &self,
+        query_id: &str,
+        timeout: Duration,
+    ) -> Result<QueryResult, SnowflakeApiError> {
+        let resp = self.wait_query_result(query_id, Some(timeout)).await?;
+        let raw = self.raw_result(Self::query_response(resp)?).await?;
+        Ok(raw.deserialize_arrow()?)
+    }
+
+    /// Waits for the query with given id to finish and returns the size of its result,
+    /// without downloading the chunks, eg to report progress while fetching them
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "test-util")]
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), snowflake_api::SnowflakeApiError> {
+    /// use serde_json::json;
+    /// use snowflake_api::transport::MockTransport;
+    /// use snowflake_api::{AuthArgs, ChunkMetadata, SnowflakeApiBuilder};
+    ///
+    /// let mock = MockTransport::new();
+    /// # mock.push_login();
+    /// mock.push_json(
+    ///     "/monitoring/queries/01b2c3d4",
+    ///     &json!({
+    ///         "code": null, "message": null, "success": true,
+    ///         "data": { "queries": [{ "id": "01b2c3d4", "status": "SUCCESS" }] }
+    ///     }),
+    /// );
+    /// let mut result = MockTransport::query_result(json!([]), json!(null));
+    /// result["data"]["total"] = json!(250_000);
+    /// result["data"]["returned"] = json!(10_000);
+    /// result["data"]["rowsetBase64"] = json!("");
+    /// result["data"]["chunks"] = json!([
+    ///     { "url": "https://stage/chunk_0", "rowCount": 120000, "uncompressedSize": 6000000 },
+    ///     { "url": "https://stage/chunk_1", "rowCount": 120000, "uncompressedSize": 5900000 }
+    /// ]);
+    /// result["data"]["qrmk"] = json!("key");
+    /// mock.push_json("/queries/01b2c3d4/result", &result);
+    /// # let auth = AuthArgs::for_tests();
+    /// let api = SnowflakeApiBuilder::new(auth)
+    ///     .with_transport(mock.clone())
+    ///     .build()?;
+    ///
+    /// let metadata = api.get_result_metadata("01b2c3d4").await?;
+    /// assert_eq!(metadata.total, 250_000);
+    /// assert_eq!(metadata.returned, 10_000);
+    /// assert_eq!(
+    ///     metadata.chunks[1],
+    ///     ChunkMetadata {
+    ///         url: "https://stage/chunk_1".to_owned(),
+    ///         row_count: 120_000,
+    ///         uncompressed_size: 5_900_000,
+    ///     }
+    /// );
+    ///
+    /// // nothing was downloaded from the chunk URLs
+    /// assert_eq!(mock.requests().len(), 3);
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "test-util"))]
+    /// # fn main() {}
+    /// ```
+    pub async fn get_result_metadata(
+        &self,
+        query_id: &str,
+    ) -> Result<ResultMetadata, SnowflakeApiError> {
+        let resp = self.wait_query_result(query_id, None).await?;
+        Ok(ResultMetadata::from_response(
+            &Self::query_response(resp)?.data,
+        ))
+    }
+
+    async fn query_status_entry(
+        &self,
+        query_id: &str,
+    ) -> Result<QueryStatusEntry, SnowflakeApiError> {
+        let parts = self.session.get_token().await?;
+
         let resp = self
-            .run_sql::<ExecResponse>(sql, QueryType::ArrowQuery)
+            .connection
+            .get::<QueryStatusResponse>(
+                QueryType::QueryStatus,
+                &self.account_identifier,
+                query_id,
+                Some(&parts.session_token_auth_header),
+            )
+            .await?;
+
+        // freshly submitted query might not be visible yet
+        Ok(resp
+            .data
+            .queries
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| QueryStatusEntry {
+                id: query_id.to_owned(),
+                status: QueryStatus::NoData,
+                error_code: None,
+                error_message: None,
+            }))
+    }
+
+    /// Waits for the query to finish, within the timeout if any, then fetches the result
+    async fn wait_query_result(
+        &self,
+        query_id: &str,
+        timeout: Option<Duration>,
+    ) -> Result<ExecResponse, SnowflakeApiError> {
+        match timeout {
+            Some(timeout) => tokio::time::timeout(timeout, self.wait_query_finished(query_id))
+                .await
+                .map_err(|_| SnowflakeApiError::Timeout(timeout))??,
+            None => self.wait_query_finished(query_id).await?,
+        }
+
+        self.fetch_query_result(query_id).await
+    }
+
+    /// Polls query status until it stops running, unknown queries fail after `MAX_NO_DATA_POLLS`
+    async fn wait_query_finished(&self, query_id: &str) -> Result<(), SnowflakeApiError> {
+        let mut delay = POLL_INTERVAL_MIN;
+        let mut no_data_polls = 0;
+        loop {
+            let entry = self.query_status_entry(query_id).await?;
+            if entry.status.is_error() {
+                return Err(SnowflakeApiError::QueryFailed(
+                    entry.id,
+                    entry.status,
+                    entry.error_code.unwrap_or_default(),
+                    entry.error_message.unwrap_or_default(),
+                ));
+            } else if entry.status == QueryStatus::NoData {
+                no_data_polls += 1;
+                if no_data_polls >= MAX_NO_DATA_POLLS {
+                    return Err(SnowflakeApiError::QueryNotFound(query_id.to_owned()));
+                }
+            } else if !entry.status.is_running() {
+                return Ok(());
+            } else {
+                no_data_polls = 0;
+            }
+
+            log::debug!(
+                "Query {query_id} is {:?}, waiting for {delay:?}",
+                entry.status
+            );
+            tokio::time::sleep(delay).await;
+            delay = (delay * 2).min(POLL_INTERVAL_MAX);
+        }
+    }
+
+    /// Fetches result of the finished query
+    async fn fetch_query_result(&self, query_id: &str) -> Result<ExecResponse, SnowflakeApiError> {
+        let parts = self.session.get_token().await?;
+        let resp = self
+            .connection
+            .get::<ExecResponse>(
+                QueryType::QueryResult,
+                &self.account_identifier,
+                &format!("{query_id}/result"),
+                Some(&parts.session_token_auth_header),
+            )
             .await?;
-        log::debug!("Got query response: {:?}", resp);
 
-        let resp = match resp {
+        Ok(resp)
+    }
+
+    fn query_response(resp: ExecResponse) -> Result<QueryExecResponse, SnowflakeApiError> {
+        match resp {
             // processable response
             ExecResponse::Query(qr) => Ok(qr),
-            ExecResponse::PutGet(_) => Err(SnowflakeApiError::UnexpectedResponse),
-            ExecResponse::Error(e) => Err(SnowflakeApiError::ApiError(
-                e.data.error_code,
-                e.message.unwrap_or_default(),
-            )),
-        }?;
+            ExecResponse::PutGet(_) | ExecResponse::Async(_) => {
+                Err(SnowflakeApiError::UnexpectedResponse)
+            }
+            ExecResponse::Error(e) => Err(e.into()),
+        }
+    }
 
+    async fn exec_arrow_raw(
+        &self,
+        sql: &str,
+        request_id: Uuid,
+    ) -> Result<RawQueryResult, SnowflakeApiError> {
+        let resp = self
+            .exec_query_response(ExecRequest::new(sql), request_id)
+            .await?;
+        self.raw_result(resp).await
+    }
+
+    /// Runs the query, waiting for the result if it was handed over to async execution
+    async fn exec_query_response(
+        &self,
+        body: ExecRequest,
+        request_id: Uuid,
+    ) -> Result<QueryExecResponse, SnowflakeApiError> {
+        let resp = self
+            .run_query::<ExecResponse>(body, QueryType::ArrowQuery, request_id)
+            .await?;
+        log::debug!("Got query response: {resp:?}");
+
+        // long-running queries are handed over to async execution after the request timeout
+        let resp = if let ExecResponse::Async(ar) = resp {
+            log::info!(
+                "Query {} is still running, polling for result",
+                ar.data.query_id
+            );
+            self.wait_query_result(&ar.data.query_id, None).await?
+        } else {
+            resp
+        };
+
+        Self::query_response(resp)
+    }
+
+    async fn raw_result(
+        &self,
+        resp: QueryExecResponse,
+    ) -> Result<RawQueryResult, SnowflakeApiError> {
         // if response was empty, base64 data is empty string
         // todo: still return empty arrow batch with proper schema? (schema always included)
         if resp.data.returned == 0 {
             log::debug!("Got response with 0 rows");
-            Ok(RawQueryResult::Empty)
-        } else if let Some(value) = resp.data.rowset {
+            return Ok(RawQueryResult::Empty);
+        }
+
+        if Self::is_json_result(&resp.data)? {
             log::debug!("Got JSON response");
             // NOTE: json response could be chunked too. however, go clients should receive arrow by-default,
             // unless user sets session variable to return json. This case was added for debugging and status
             // information being passed through that fields.
-            Ok(RawQueryResult::Json(JsonResult {
+            let value = resp.data.rowset.ok_or(SnowflakeApiError::BrokenResponse)?;
+            return Ok(RawQueryResult::Json(JsonResult {
                 value,
                 schema: resp.data.rowtype.into_iter().map(Into::into).collect(),
-            }))
-        } else if let Some(base64) = resp.data.rowset_base64 {
-            // fixme: is it possible to give streaming interface?
-            let mut chunks = try_join_all(resp.data.chunks.iter().map(|chunk| {
+            }));
+        }
+
+        let base64 = resp
+            .data
+            .rowset_base64
+            .ok_or(SnowflakeApiError::BrokenResponse)?;
+        // inline rowset is the first chunk of the result, when it's not empty
+        let mut chunks = Vec::with_capacity(resp.data.chunks.len() + 1);
+        if !base64.is_empty() {
+            log::debug!("Got base64 encoded response");
+            let bytes = base64::engine::general_purpose::STANDARD.decode(base64)?;
+            chunks.push(Bytes::from(bytes));
+        }
+        // fixme: is it possible to give streaming interface?
+        chunks.extend(
+            try_join_all(resp.data.chunks.iter().map(|chunk| {
                 self.connection
                     .get_chunk(&chunk.url, &resp.data.chunk_headers)
             }))
-            .await?;
+            .await?,
+        );
 
-            // fixme: should base64 chunk go first?
-            // fixme: if response is chunked is it both base64 + chunks or just chunks?
-            if !base64.is_empty() {
-                log::debug!("Got base64 encoded response");
-                let bytes = Bytes::from(base64::engine::general_purpose::STANDARD.decode(base64)?);
-                chunks.push(bytes);
-            }
+        Ok(RawQueryResult::Bytes(chunks))
+    }
 
-            Ok(RawQueryResult::Bytes(chunks))
-        } else {
-            Err(SnowflakeApiError::BrokenResponse)
+    /// Whether the rowset is JSON rather than Arrow, failing for missing or unknown formats
+    /// which can't be decoded
+    fn is_json_result(data: &QueryExecResponseData) -> Result<bool, SnowflakeApiError> {
+        match data.result_format() {
+            Some(QueryResultFormat::Json) => Ok(true),
+            Some(QueryResultFormat::Arrow) => Ok(false),
+            Some(QueryResultFormat::Unknown) => Err(SnowflakeApiError::Unimplemented(
+                "results in formats other than JSON and Arrow".to_owned(),
+            )),
+            None => Err(SnowflakeApiError::BrokenResponse),
         }
     }
 
-    async fn run_sql<R: serde::de::DeserializeOwned>(
+    async fn run_query<R: serde::de::DeserializeOwned>(
         &self,
-        sql_text: &str,
+        mut body: ExecRequest,
         query_type: QueryType,
+        request_id: Uuid,
     ) -> Result<R, SnowflakeApiError> {
-        log::debug!("Executing: {}", sql_text);
+        log::debug!("Executing: {}", body.sql_text);
+
+        let span = trace::Span::query(request_id);
+        let resp = span
+            .instrument(async {
+                let resp = self.run_sql_once(&mut body, query_type, request_id).await?;
+
+                // session token could be invalidated before its validity elapsed, renew it and retry once
+                if resp.get("code").and_then(serde_json::Value::as_str)
+                    == Some(SESSION_EXPIRED_CODE)
+                {
+                    log::info!("Session token has expired, renewing");
+                    self.session.renew_session().await?;
+                    self.run_sql_once(&mut body, query_type, request_id).await
+                } else {
+                    Ok(resp)
+                }
+            })
+            .await;
+        span.record_sequence_id(body.sequence_id);
+        if let Some(query_id) = resp
+            .as_ref()
+            .ok()
+            .and_then(|r| r["data"]["queryId"].as_str())
+        {
+            span.record_query_id(query_id);
+        }
+        let resp = span.finish(resp)?;
 
-        let parts = self.session.get_token().await?;
+        serde_json::from_value(resp)
+            .map_err(|e| SnowflakeApiError::RequestError(ConnectionError::Deserialization(e)))
+    }
 
-        let body = ExecRequest {
-            sql_text: sql_text.to_string(),
-            async_exec: false,
-            sequence_id: parts.sequence_id,
-            is_internal: false,
-        };
+    async fn run_sql_once(
+        &self,
+        body: &mut ExecRequest,
+        query_type: QueryType,
+        request_id: Uuid,
+    ) -> Result<serde_json::Value, SnowflakeApiError> {
+        let parts = self.session.get_token().await?;
+        body.sequence_id = self.session.next_sequence_id();
 
         let resp = self
             .connection
-            .request::<R>(
+            .request_with_id::<serde_json::Value>(
                 query_type,
                 &self.account_identifier,
                 &[],
                 Some(&parts.session_token_auth_header),
-                body,
+                &*body,
+                request_id,
             )
             .await?;
 
         Ok(resp)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use arrow::array::{ArrayRef, AsArray, Int64Array};
+    use arrow::datatypes::Int64Type;
+    use arrow::ipc::writer::StreamWriter;
+    use arrow::record_batch::RecordBatch;
+    use base64::Engine;
+    use futures::TryStreamExt;
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+    use uuid::Uuid;
+
+    use crate::transport::{MockTransport, TransportRequest, TransportResponse};
+    use crate::{
+        AuthArgs, ExecRequestBuilder, FetchOptions, QueryResult, QueryStatus, SnowflakeApi,
+        SnowflakeApiBuilder, SnowflakeApiError,
+    };
+
+    const QUERY_PATH: &str = "/queries/v1/query-request";
+    const STATUS_PATH: &str = "/monitoring/queries/01b2c3d4";
+
+    fn api(mock: &MockTransport) -> SnowflakeApi {
+        SnowflakeApiBuilder::new(AuthArgs::for_tests())
+            .with_transport(mock.clone())
+            .build()
+            .unwrap()
+    }
+
+    fn paths(mock: &MockTransport) -> Vec<String> {
+        mock.requests()
+            .iter()
+            .map(|r| r.url.path().to_owned())
+            .collect()
+    }
+
+    fn param(request: &TransportRequest, name: &str) -> Option<String> {
+        request
+            .url
+            .query_pairs()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.into_owned())
+    }
+
+    /// Arrow IPC stream of a single `N` column with the given values
+    fn ipc(values: impl IntoIterator<Item = i64>) -> Vec<u8> {
+        let values: ArrayRef = Arc::new(Int64Array::from_iter_values(values));
+        let batch = RecordBatch::try_from_iter([("N", values)]).unwrap();
+        let mut writer = StreamWriter::try_new(Vec::new(), &batch.schema()).unwrap();
+        writer.write(&batch).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    /// `SELECT N` split in 3 chunks of 10 rows each, with nothing in the first response
+    fn push_chunked_numbers(mock: &MockTransport) {
+        let mut result = MockTransport::query_result(
+            json!([{ "name": "N", "type": "fixed", "scale": 0, "precision": 18, "nullable": false }]),
+            json!(null),
+        );
+        result["data"]["rowsetBase64"] = json!("");
+        result["data"]["total"] = json!(30);
+        result["data"]["returned"] = json!(30);
+        result["data"]["chunks"] = (0..3)
+            .map(|i| {
+                json!({
+                    "url": format!("https://stage.example.com/chunk{i}"),
+                    "rowCount": 10,
+                    "uncompressedSize": 100
+                })
+            })
+            .collect();
+        mock.push_json(QUERY_PATH, &result);
+        for i in 0..3 {
+            mock.push_response(
+                &format!("/chunk{i}"),
+                TransportResponse {
+                    status: StatusCode::OK,
+                    body: ipc(i * 10..(i + 1) * 10).into(),
+                },
+            );
+        }
+    }
+
+    fn status(status: &str) -> Value {
+        json!({
+            "code": null, "message": null, "success": true,
+            "data": { "queries": [{ "id": "01b2c3d4", "status": status }] }
+        })
+    }
+
+    #[tokio::test]
+    async fn concurrent_statements_get_distinct_sequence_ids() {
+        let mock = MockTransport::new();
+        mock.push_login();
+        for _ in 0..64 {
+            mock.push_query_result(json!([]), json!([]));
+        }
+        let api = Arc::new(api(&mock));
+
+        let tasks: Vec<_> = (0..64)
+            .map(|i| {
+                let api = Arc::clone(&api);
+                tokio::spawn(async move { api.exec(&format!("INSERT INTO T VALUES ({i})")).await })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap().unwrap();
+        }
+
+        let sequence_ids: HashSet<_> = mock.requests()[1..]
+            .iter()
+            .map(|r| r.json_body().unwrap()["sequenceId"].as_u64().unwrap())
+            .collect();
+        assert_eq!(sequence_ids, (1..=64).collect());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn heartbeat_runs_until_the_handle_is_dropped() {
+        let mock = MockTransport::new();
+        mock.push_login();
+        mock.push_query_result(json!([]), json!([]));
+        for _ in 0..10 {
+            mock.push_json(
+                "/session/heartbeat",
+                &json!({ "code": null, "message": null, "success": true, "data": null }),
+            );
+        }
+        let api = api(&mock);
+        let heartbeats = || {
+            paths(&mock)
+                .iter()
+                .filter(|p| *p == "/session/heartbeat")
+                .count()
+        };
+
+        api.exec("SELECT 1").await.unwrap();
+        let interval = Duration::from_secs(30);
+        let handle = api.start_heartbeat(interval);
+
+        tokio::time::sleep(Duration::from_secs(29)).await;
+        assert_eq!(heartbeats(), 0);
+        tokio::time::sleep(Duration::from_secs(62)).await;
+        assert_eq!(heartbeats(), 3);
+
+        let requests = mock.requests();
+        let heartbeat = requests.last().unwrap();
+        assert_eq!(
+            heartbeat.headers["authorization"],
+            r#"Snowflake Token="session-token""#
+        );
+
+        drop(handle);
+        tokio::time::sleep(interval * 4).await;
+        assert_eq!(heartbeats(), 3);
+    }
+
+    #[tokio::test]
+    async fn use_warehouse_is_internal() {
+        let mock = MockTransport::new();
+        mock.push_login();
+        for _ in 0..2 {
+            mock.push_query_result(json!([]), json!([]));
+        }
+        let api = api(&mock);
+
+        api.use_warehouse("compute_wh").await.unwrap();
+        api.exec("SELECT 1").await.unwrap();
+
+        let requests = mock.requests();
+        let statement = requests[1].json_body().unwrap();
+        assert_eq!(statement["sqlText"], "USE WAREHOUSE COMPUTE_WH");
+        assert_eq!(statement["isInternal"], true);
+        assert_eq!(requests[2].json_body().unwrap()["isInternal"], false);
+    }
+
+    #[tokio::test]
+    async fn exec_follows_query_result_format() {
+        let mock = MockTransport::new();
+        mock.push_login();
+        let rowtype = json!([{ "name": "N", "type": "fixed", "scale": 0, "precision": 18, "nullable": false }]);
+        let arrow_rowset = base64::engine::general_purpose::STANDARD.encode(ipc([1]));
+        let mut json_result = MockTransport::query_result(rowtype.clone(), json!([["1"]]));
+        json_result["data"]["queryResultFormat"] = json!("json");
+        mock.push_json(QUERY_PATH, &json_result);
+        for format in ["arrow", "parquet"] {
+            let mut result = MockTransport::query_result(rowtype.clone(), json!(null));
+            result["data"]["queryResultFormat"] = json!(format);
+            result["data"]["rowsetBase64"] = json!(arrow_rowset);
+            result["data"]["total"] = json!(1);
+            result["data"]["returned"] = json!(1);
+            mock.push_json(QUERY_PATH, &result);
+        }
+        let api = api(&mock);
+
+        let result = api.exec("SELECT 1 AS N").await.unwrap();
+        assert!(matches!(result, QueryResult::Json(j) if j.value == json!([["1"]])));
+
+        let result = api.exec("SELECT 1 AS N").await.unwrap();
+        assert!(matches!(result, QueryResult::Arrow(b) if b[0].num_rows() == 1));
+
+        // formats this client can't decode are reported rather than guessed
+        let result = api.exec("SELECT 1 AS N").await;
+        assert!(matches!(result, Err(SnowflakeApiError::Unimplemented(_))));
+    }
+
+    #[tokio::test]
+    async fn query_arrow_converts_mixed_columns() {
+        use std::collections::HashMap;
+
+        use arrow::array::{Array, Int32Array, StringArray, StructArray};
+        use arrow::datatypes::{
+            DataType, Decimal128Type, Field, Fields, Schema, TimeUnit, TimestampNanosecondType,
+        };
+
+        let field = |name: &str, data_type, logical_type: &str, scale: &str| {
+            let metadata = HashMap::from([
+                ("logicalType".to_owned(), logical_type.to_owned()),
+                ("precision".to_owned(), "10".to_owned()),
+                ("scale".to_owned(), scale.to_owned()),
+            ]);
+            Field::new(name, data_type, true).with_metadata(metadata)
+        };
+        let timestamp_fields = Fields::from(vec![
+            Field::new("epoch", DataType::Int64, false),
+            Field::new("fraction", DataType::Int32, false),
+        ]);
+        let schema = Arc::new(Schema::new(vec![
+            field("PRICE", DataType::Int64, "FIXED", "2"),
+            field("NAME", DataType::Utf8, "TEXT", "0"),
+            field(
+                "CREATED",
+                DataType::Struct(timestamp_fields.clone()),
+                "TIMESTAMP_NTZ",
+                "9",
+            ),
+            field("UPDATED", DataType::Int64, "TIMESTAMP_LTZ", "3"),
+        ]));
+        let created = StructArray::new(
+            timestamp_fields,
+            vec![
+                Arc::new(Int64Array::from(vec![1_700_000_000, 1_700_000_001])),
+                Arc::new(Int32Array::from(vec![500, 0])),
+            ],
+            None,
+        );
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(Int64Array::from(vec![150, 2_599])),
+            Arc::new(StringArray::from(vec!["apple", "banana"])),
+            Arc::new(created),
+            Arc::new(Int64Array::from(vec![Some(1_700_000_000_123), None])),
+        ];
+        let batch = RecordBatch::try_new(Arc::clone(&schema), columns).unwrap();
+        let mut writer = StreamWriter::try_new(Vec::new(), &schema).unwrap();
+        writer.write(&batch).unwrap();
+        let rowset = base64::engine::general_purpose::STANDARD.encode(writer.into_inner().unwrap());
+
+        let mock = MockTransport::new();
+        mock.push_login();
+        let mut result = MockTransport::query_result(json!([]), json!(null));
+        result["data"]["queryResultFormat"] = json!("arrow");
+        result["data"]["rowsetBase64"] = json!(rowset);
+        result["data"]["total"] = json!(2);
+        result["data"]["returned"] = json!(2);
+        mock.push_json(QUERY_PATH, &result);
+        let api = api(&mock);
+
+        let batches = api
+            .query_arrow("SELECT PRICE, NAME, CREATED, UPDATED FROM PRODUCTS")
+            .await
+            .unwrap();
+
+        let body = mock.requests()[1].json_body().unwrap();
+        assert_eq!(body["parameters"]["QUERY_RESULT_FORMAT"], "ARROW");
+
+        let batch = &batches[0];
+        let prices = batch.column(0).as_primitive::<Decimal128Type>();
+        assert_eq!(prices.data_type(), &DataType::Decimal128(10, 2));
+        assert_eq!(prices.value_as_string(1), "25.99");
+        assert_eq!(batch.column(1).as_string::<i32>().value(0), "apple");
+        let created = batch.column(2).as_primitive::<TimestampNanosecondType>();
+        assert_eq!(
+            created.data_type(),
+            &DataType::Timestamp(TimeUnit::Nanosecond, None)
+        );
+        assert_eq!(created.value(0), 1_700_000_000_000_000_500);
+        let updated = batch.column(3).as_primitive::<TimestampNanosecondType>();
+        assert_eq!(
+            updated.data_type(),
+            &DataType::Timestamp(TimeUnit::Nanosecond, Some("UTC".into()))
+        );
+        assert_eq!(updated.value(0), 1_700_000_000_123_000_000);
+        assert!(updated.is_null(1));
+    }
+
+    #[tokio::test]
+    async fn exec_and_stream_reject_the_same_formats() {
+        let mock = MockTransport::new();
+        mock.push_login();
+        let mut parquet = MockTransport::query_result(json!([]), json!(null));
+        parquet["data"]["queryResultFormat"] = json!("parquet");
+        parquet["data"]["rowsetBase64"] = json!("");
+        parquet["data"]["returned"] = json!(1);
+        // neither the format nor a rowset to infer it from
+        let mut missing = MockTransport::query_result(json!([]), json!(null));
+        missing["data"]["returned"] = json!(1);
+        for result in [&parquet, &parquet, &missing, &missing] {
+            mock.push_json(QUERY_PATH, result);
+        }
+        let api = api(&mock);
+
+        let exec = api.exec("SELECT 1").await;
+        let stream = api.exec_stream("SELECT 1").await;
+        assert!(matches!(exec, Err(SnowflakeApiError::Unimplemented(_))));
+        assert!(matches!(stream, Err(SnowflakeApiError::Unimplemented(_))));
+
+        let exec = api.exec("SELECT 1").await;
+        let stream = api.exec_stream("SELECT 1").await;
+        assert!(matches!(exec, Err(SnowflakeApiError::BrokenResponse)));
+        assert!(matches!(stream, Err(SnowflakeApiError::BrokenResponse)));
+    }
+
+    #[tokio::test]
+    async fn expired_session_is_renewed_with_the_same_request_id() {
+        let mock = MockTransport::new();
+        mock.push_login();
+        mock.push_json(
+            QUERY_PATH,
+            &json!({ "code": "390112", "message": "Session expired", "success": false, "data": null }),
+        );
+        mock.push_json(
+            "/session/token-request",
+            &json!({
+                "code": null, "message": null, "success": true,
+                "data": {
+                    "sessionToken": "renewed-token", "validityInSecondsST": 3600,
+                    "masterToken": "master-token", "validityInSecondsMT": 14400, "sessionId": 1
+                }
+            }),
+        );
+        mock.push_query_result(json!([]), json!([]));
+        let api = api(&mock);
+
+        let request_id = Uuid::new_v4();
+        api.exec_with_request_id("INSERT INTO ORDERS VALUES (1)", request_id)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            paths(&mock),
+            [
+                "/session/v1/login-request",
+                QUERY_PATH,
+                "/session/token-request",
+                QUERY_PATH
+            ]
+        );
+        let attempts: Vec<_> = mock
+            .requests()
+            .into_iter()
+            .filter(|r| r.url.path() == QUERY_PATH)
+            .collect();
+        for attempt in &attempts {
+            assert_eq!(param(attempt, "requestId"), Some(request_id.to_string()));
+        }
+        assert_ne!(
+            param(&attempts[0], "request_guid"),
+            param(&attempts[1], "request_guid")
+        );
+    }
+
+    #[tokio::test]
+    async fn execute_multi_fetches_each_result() {
+        let mock = MockTransport::new();
+        mock.push_login();
+        let mut parent = MockTransport::query_result(
+            json!([{ "name": "multiple statement execution", "type": "text", "nullable": false }]),
+            json!([["Multiple statements executed successfully."]]),
+        );
+        parent["data"]["resultIds"] = json!("01b2c3d5,01b2c3d6");
+        mock.push_json(QUERY_PATH, &parent);
+        let mut insert = MockTransport::query_result(
+            json!([{ "name": "number of rows inserted", "type": "fixed", "scale": 0, "precision": 19, "nullable": false }]),
+            json!([["2"]]),
+        );
+        insert["data"]["queryId"] = json!("01b2c3d5");
+        mock.push_json("/queries/01b2c3d5/result", &insert);
+        let mut select = MockTransport::query_result(
+            json!([{ "name": "ID", "type": "fixed", "scale": 0, "precision": 18, "nullable": false }]),
+            json!([["1"], ["2"]]),
+        );
+        select["data"]["queryId"] = json!("01b2c3d6");
+        mock.push_json("/queries/01b2c3d6/result", &select);
+        let api = api(&mock);
+
+        let results = api
+            .execute_multi(&["INSERT INTO T VALUES (1), (2);", "SELECT ID FROM T"])
+            .await
+            .unwrap();
+
+        let values: Vec<_> = results
+            .into_iter()
+            .map(|result| match result {
+                QueryResult::Json(j) => j.value,
+                _ => panic!("expected JSON result"),
+            })
+            .collect();
+        assert_eq!(values, [json!([["2"]]), json!([["1"], ["2"]])]);
+
+        let body = mock.requests()[1].json_body().unwrap();
+        assert_eq!(
+            body["sqlText"],
+            "INSERT INTO T VALUES (1), (2);\nSELECT ID FROM T"
+        );
+        assert_eq!(body["parameters"]["MULTI_STATEMENT_COUNT"], 2);
+        assert_eq!(
+            paths(&mock)[2..],
+            ["/queries/01b2c3d5/result", "/queries/01b2c3d6/result"]
+        );
+    }
+
+    #[tokio::test]
+    async fn execute_multi_without_result_ids_returns_single_result() {
+        let mock = MockTransport::new();
+        mock.push_login();
+        mock.push_query_result(
+            json!([{ "name": "ONE", "type": "fixed", "scale": 0, "precision": 1, "nullable": false }]),
+            json!([["1"]]),
+        );
+        let api = api(&mock);
+
+        let results = api.execute_multi(&["SELECT 1 AS ONE"]).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(matches!(&results[0], QueryResult::Json(j) if j.value == json!([["1"]])));
+
+        // nothing is sent for an empty batch
+        let result = api.execute_multi(&[]).await;
+        assert!(matches!(result, Err(SnowflakeApiError::NoStatements)));
+        assert_eq!(mock.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn max_rows_stops_downloading_chunks() {
+        let mock = MockTransport::new();
+        mock.push_login();
+        push_chunked_numbers(&mock);
+        let api = api(&mock);
+
+        let options = FetchOptions::default().max_rows(10).prefetch(2);
+        let batches: Vec<_> = api
+            .exec_stream_with_options("SELECT N FROM NUMBERS", &options)
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+
+        assert_eq!(batches.iter().map(RecordBatch::num_rows).sum::<usize>(), 10);
+        assert_eq!(
+            paths(&mock),
+            ["/session/v1/login-request", QUERY_PATH, "/chunk0"]
+        );
+    }
+
+    #[tokio::test]
+    async fn prefetched_chunks_keep_their_order() {
+        let mock = MockTransport::new();
+        mock.push_login();
+        push_chunked_numbers(&mock);
+        let api = api(&mock);
+
+        let options = FetchOptions::default().prefetch(2);
+        let batches: Vec<RecordBatch> = api
+            .exec_stream_with_options("SELECT N FROM NUMBERS", &options)
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+
+        let rows: Vec<i64> = batches
+            .iter()
+            .flat_map(|batch| {
+                batch
+                    .column(0)
+                    .as_primitive::<Int64Type>()
+                    .values()
+                    .to_vec()
+            })
+            .collect();
+        assert_eq!(rows, (0..30).collect::<Vec<_>>());
+        assert_eq!(mock.requests().len(), 5);
+    }
+
+    #[tokio::test]
+    async fn load_table_stages_copies_and_drops() {
+        let mock = MockTransport::new();
+        mock.push_login();
+        let message = |text: &str| {
+            MockTransport::query_result(
+                json!([{ "name": "status", "type": "text", "nullable": true }]),
+                json!([[text]]),
+            )
+        };
+        mock.push_json(QUERY_PATH, &message("Stage area successfully created."));
+        // no local files match the pattern, so nothing is uploaded to the stage bucket
+        mock.push_json(
+            QUERY_PATH,
+            &json!({
+                "code": null, "message": null, "success": true,
+                "data": {
+                    "command": "UPLOAD",
+                    "src_locations": ["/nonexistent/orders_*.csv"],
+                    "parallel": 4, "threshold": 200_000_000,
+                    "autoCompress": true, "overwrite": false, "sourceCompression": "auto_detect",
+                    "stageInfo": {
+                        "locationType": "S3", "location": "bucket/stage/", "region": "us-west-2",
+                        "creds": {
+                            "AWS_KEY_ID": "id", "AWS_SECRET_KEY": "secret", "AWS_TOKEN": "token",
+                            "AWS_ID": "id", "AWS_KEY": "key"
+                        }
+                    },
+                    "encryptionMaterial": { "queryStageMasterKey": "a2V5", "queryId": "01b2c3d4", "smkId": 1 }
+                }
+            }),
+        );
+        let columns = [
+            "file",
+            "status",
+            "rows_parsed",
+            "rows_loaded",
+            "errors_seen",
+            "first_error",
+        ]
+        .map(|name| json!({ "name": name, "type": "text", "nullable": true }));
+        mock.push_query_result(
+            json!(columns),
+            json!([
+                ["orders_1.csv.gz", "LOADED", "100", "100", "0", null],
+                ["orders_2.csv.gz", "LOADED", "42", "42", "0", null],
+                [
+                    "orders_3.csv.gz",
+                    "PARTIALLY_LOADED",
+                    "10",
+                    "8",
+                    "2",
+                    "Numeric value 'abc' is not recognized"
+                ]
+            ]),
+        );
+        mock.push_json(
+            QUERY_PATH,
+            &message("SNOWFLAKE_RS_LOAD successfully dropped."),
+        );
+        let api = api(&mock);
+
+        let result = api
+            .load_table_with_options(
+                "ORDERS",
+                "/nonexistent/orders_*.csv",
+                "TYPE = CSV SKIP_HEADER = 1",
+                "ON_ERROR = CONTINUE",
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.files.len(), 3);
+        assert_eq!(result.rows_loaded(), 150);
+        assert_eq!(result.errors_seen(), 2);
+        let failed: Vec<_> = result
+            .failed_files()
+            .map(|f| (f.file.as_str(), f.status.as_str(), f.first_error.as_deref()))
+            .collect();
+        let error = Some("Numeric value 'abc' is not recognized");
+        assert_eq!(failed, [("orders_3.csv.gz", "PARTIALLY_LOADED", error)]);
+
+        let bodies: Vec<_> = mock.requests()[1..]
+            .iter()
+            .map(|r| r.json_body().unwrap())
+            .collect();
+        let statements: Vec<_> = bodies
+            .iter()
+            .map(|body| body["sqlText"].as_str().unwrap())
+            .collect();
+        let stage = statements[0]
+            .strip_prefix("CREATE TEMPORARY STAGE ")
+            .unwrap();
+        assert_eq!(
+            statements[1..],
+            [
+                format!("PUT 'file:///nonexistent/orders_*.csv' @{stage} AUTO_COMPRESS=TRUE OVERWRITE=FALSE"),
+                format!("COPY INTO ORDERS FROM @{stage} FILE_FORMAT = (TYPE = CSV SKIP_HEADER = 1) ON_ERROR = CONTINUE"),
+                format!("DROP STAGE IF EXISTS {stage}"),
+            ]
+        );
+        // the temporary stage is managed by the client
+        let internal: Vec<_> = bodies
+            .iter()
+            .map(|body| body["isInternal"].as_bool().unwrap())
+            .collect();
+        assert_eq!(internal, [true, false, false, true]);
+    }
+
+    #[tokio::test]
+    async fn cancel_query_sends_abort_request() {
+        let mock = MockTransport::new();
+        mock.push_login();
+        mock.push_json(
+            QUERY_PATH,
+            &json!({
+                "code": "333334", "message": null, "success": true,
+                "data": { "queryId": "01b2c3d4", "getResultUrl": "/queries/01b2c3d4/result" }
+            }),
+        );
+        mock.push_json(
+            "/queries/v1/abort-request",
+            &json!({ "code": null, "message": null, "success": true, "data": null }),
+        );
+        let api = api(&mock);
+
+        let request = ExecRequestBuilder::new()
+            .sql("CALL long_running()")
+            .async_exec(true);
+        let query = api.exec_query_request(request).await.unwrap();
+        api.cancel_query(query.request_id).await.unwrap();
+
+        let abort = mock.requests().pop().unwrap();
+        assert_eq!(abort.url.path(), "/queries/v1/abort-request");
+        let body = abort.json_body().unwrap();
+        assert_eq!(body["requestId"], query.request_id.to_string());
+        assert_eq!(body["sqlText"], "");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn polls_until_query_succeeds() {
+        let mock = MockTransport::new();
+        mock.push_login();
+        for s in ["QUEUED", "RUNNING", "SUCCESS"] {
+            mock.push_json(STATUS_PATH, &status(s));
+        }
+        mock.push_json(
+            "/queries/01b2c3d4/result",
+            &MockTransport::query_result(
+                json!([{ "name": "ONE", "type": "fixed", "scale": 0, "precision": 1, "nullable": false }]),
+                json!([["1"]]),
+            ),
+        );
+        let api = api(&mock);
+
+        let QueryResult::Json(result) = api.get_query_result("01b2c3d4").await.unwrap() else {
+            panic!("expected JSON result");
+        };
+        assert_eq!(result.value, json!([["1"]]));
+
+        let paths = paths(&mock);
+        let polls = paths.iter().filter(|p| *p == STATUS_PATH).count();
+        assert_eq!(polls, 3);
+        assert_eq!(paths.last().unwrap(), "/queries/01b2c3d4/result");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn polling_stops_at_failed_status() {
+        let mock = MockTransport::new();
+        mock.push_login();
+        mock.push_json(STATUS_PATH, &status("RUNNING"));
+        mock.push_json(
+            STATUS_PATH,
+            &json!({
+                "code": null, "message": null, "success": true,
+                "data": { "queries": [{
+                    "id": "01b2c3d4",
+                    "status": "FAILED_WITH_ERROR",
+                    "errorCode": "100038",
+                    "errorMessage": "Numeric value 'abc' is not recognized"
+                }] }
+            }),
+        );
+        let api = api(&mock);
+
+        let Err(SnowflakeApiError::QueryFailed(id, status, code, message)) =
+            api.get_query_result("01b2c3d4").await
+        else {
+            panic!("expected a failed query");
+        };
+        assert_eq!(id, "01b2c3d4");
+        assert_eq!(status, QueryStatus::FailedWithError);
+        assert_eq!(code, "100038");
+        assert_eq!(message, "Numeric value 'abc' is not recognized");
+
+        let paths = paths(&mock);
+        assert_eq!(paths.iter().filter(|p| *p == STATUS_PATH).count(), 2);
+        assert!(!paths.iter().any(|p| p.ends_with("/result")));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn polling_times_out() {
+        let mock = MockTransport::new();
+        mock.push_login();
+        for _ in 0..10 {
+            mock.push_json(STATUS_PATH, &status("RUNNING"));
+        }
+        let api = api(&mock);
+
+        let timeout = Duration::from_secs(10);
+        let result = api.get_query_result_with_timeout("01b2c3d4", timeout).await;
+        assert!(matches!(result, Err(SnowflakeApiError::Timeout(t)) if t == timeout));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn client_timeout_aborts_the_query() {
+        let mock = MockTransport::new();
+        mock.push_login();
+        mock.push_json(
+            QUERY_PATH,
+            &json!({
+                "code": "333334", "message": null, "success": true,
+                "data": { "queryId": "01b2c3d4", "getResultUrl": "/queries/01b2c3d4/result" }
+            }),
+        );
+        for _ in 0..100 {
+            mock.push_json(STATUS_PATH, &status("RUNNING"));
+        }
+        mock.push_json(
+            "/queries/v1/abort-request",
+            &json!({ "code": null, "message": null, "success": true, "data": null }),
+        );
+        let api = api(&mock);
+
+        let timeout = Duration::from_millis(2500);
+        let request = ExecRequestBuilder::new()
+            .sql("CALL long_running()")
+            .timeout(timeout);
+        let result = api.exec_query_request(request).await;
+        assert!(matches!(result, Err(SnowflakeApiError::Timeout(t)) if t == timeout));
+
+        let requests = mock.requests();
+        let query = &requests[1];
+        assert_eq!(
+            query.json_body().unwrap()["parameters"]["STATEMENT_TIMEOUT_IN_SECONDS"],
+            3
+        );
+        let abort = requests.last().unwrap();
+        assert_eq!(abort.url.path(), "/queries/v1/abort-request");
+        assert_eq!(
+            Some(
+                abort.json_body().unwrap()["requestId"]
+                    .as_str()
+                    .unwrap()
+                    .to_owned()
+            ),
+            param(query, "requestId")
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn statement_timeout_error_is_a_timeout() {
+        let mock = MockTransport::new();
+        mock.push_login();
+        mock.push_json(
+            QUERY_PATH,
+            &json!({
+                "code": "000630",
+                "message": "Statement reached its statement or warehouse timeout of 1 second(s) and was canceled.",
+                "success": false,
+                "data": {
+                    "age": 0,
+                    "errorCode": "000630",
+                    "internalError": false,
+                    "sqlState": "57014",
+                    "queryId": "01b2c3d4"
+                }
+            }),
+        );
+        let api = api(&mock);
+
+        let timeout = Duration::from_secs(1);
+        let request = ExecRequestBuilder::new()
+            .sql("CALL long_running()")
+            .timeout(timeout);
+        let result = api.exec_query_request(request).await;
+        assert!(matches!(result, Err(SnowflakeApiError::Timeout(t)) if t == timeout));
+
+        // the server already cancelled the query
+        assert_eq!(paths(&mock), ["/session/v1/login-request", QUERY_PATH]);
+    }
+}
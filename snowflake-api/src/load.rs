@@ -0,0 +1,94 @@
+use crate::arrow_types::text_rows;
+use crate::{QueryResult, SnowflakeApiError};
+
+/// Outcome of [`crate::SnowflakeApi::load_table`], as reported by `COPY INTO`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LoadResult {
+    /// One entry per file, empty if there was nothing to load
+    pub files: Vec<FileLoadResult>,
+}
+
+impl LoadResult {
+    /// Rows loaded from all files
+    pub fn rows_loaded(&self) -> u64 {
+        self.files.iter().map(|f| f.rows_loaded).sum()
+    }
+
+    /// Rejected records of all files
+    pub fn errors_seen(&self) -> u64 {
+        self.files.iter().map(|f| f.errors_seen).sum()
+    }
+
+    /// Files which were not loaded completely
+    pub fn failed_files(&self) -> impl Iterator<Item = &FileLoadResult> {
+        self.files.iter().filter(|f| f.status != "LOADED")
+    }
+}
+
+/// Load statistics of a single file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileLoadResult {
+    pub file: String,
+    /// `LOADED`, `PARTIALLY_LOADED`, `LOAD_FAILED` or `LOAD_SKIPPED`
+    pub status: String,
+    pub rows_parsed: u64,
+    pub rows_loaded: u64,
+    pub errors_seen: u64,
+    pub first_error: Option<String>,
+    pub first_error_line: Option<u64>,
+    pub first_error_column_name: Option<String>,
+}
+
+/// Result set of `COPY INTO <table>`, one row per file.
+/// When no files were processed there is a single `status` column with a message instead.
+pub fn load_result(result: QueryResult) -> Result<LoadResult, SnowflakeApiError> {
+    let (columns, rows) = match result {
+        QueryResult::Empty => return Ok(LoadResult::default()),
+        QueryResult::Json(json) => {
+            let columns = json.schema.iter().map(|f| f.name.clone()).collect();
+            let rows = json
+                .rows()
+                .map(|row| (0..row.len()).map(|i| row.get(i)).collect())
+                .collect::<Result<_, _>>()
+                .map_err(|_| SnowflakeApiError::UnexpectedResponse)?;
+            (columns, rows)
+        }
+        QueryResult::Arrow(batches) => text_rows(&batches)?,
+    };
+
+    let column = |name: &str| columns.iter().position(|c| c.eq_ignore_ascii_case(name));
+    let Some(file) = column("file") else {
+        return Ok(LoadResult::default());
+    };
+    let [status, rows_parsed, rows_loaded, errors_seen, first_error, first_error_line, first_error_column_name] =
+        [
+            "status",
+            "rows_parsed",
+            "rows_loaded",
+            "errors_seen",
+            "first_error",
+            "first_error_line",
+            "first_error_column_name",
+        ]
+        .map(column);
+
+    let files = rows
+        .into_iter()
+        .map(|row: Vec<Option<String>>| {
+            let text = |i: Option<usize>| i.and_then(|i| row.get(i).cloned().flatten());
+            let count = |i| text(i).and_then(|v| v.parse().ok());
+            FileLoadResult {
+                file: text(Some(file)).unwrap_or_default(),
+                status: text(status).unwrap_or_default(),
+                rows_parsed: count(rows_parsed).unwrap_or_default(),
+                rows_loaded: count(rows_loaded).unwrap_or_default(),
+                errors_seen: count(errors_seen).unwrap_or_default(),
+                first_error: text(first_error),
+                first_error_line: count(first_error_line),
+                first_error_column_name: text(first_error_column_name),
+            }
+        })
+        .collect();
+
+    Ok(LoadResult { files })
+}
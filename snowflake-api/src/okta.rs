@@ -0,0 +1,182 @@
+//! Checks of the native Okta flow.
+//!
+//! Credentials are sent to the token URL returned by Snowflake, and the SSO page returned for
+//! the one-time token is forwarded to Snowflake as the SAML response. Both the URLs and the target
+//! of the SAML form are verified, so that credentials only go to the configured Okta organization
+//! and the SAML response is only meant for the account being logged into.
+
+use regex::Regex;
+use url::Url;
+
+/// Whether the URL has the same scheme, host and port as the authenticator URL
+pub fn same_origin(authenticator: &Url, url: &str) -> bool {
+    Url::parse(url).is_ok_and(|url| url.origin() == authenticator.origin())
+}
+
+/// Target of the SAML form of the SSO page, it is HTML-escaped in the `action` attribute
+pub fn saml_postback_url(html: &str) -> Option<Url> {
+    let form_re = Regex::new(r#"(?is)<form[^>]*\saction="([^"]*)""#).unwrap();
+    let action = form_re.captures(html)?.get(1)?;
+    Url::parse(&unescape_html(action.as_str())).ok()
+}
+
+/// Whether the SAML response is posted back to the account, eg `https://myorg-account.snowflakecomputing.com/fed/login`
+pub fn is_account_postback(postback: &Url, account_identifier: &str) -> bool {
+    let host = format!("{account_identifier}.snowflakecomputing.com");
+    postback.scheme() == "https"
+        && postback
+            .host_str()
+            .is_some_and(|h| h.eq_ignore_ascii_case(&host))
+}
+
+/// Resolves character references, eg `https&#x3a;&#x2f;&#x2f;`, and the common named entities
+fn unescape_html(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let resolved = rest.find(';').and_then(|end| {
+            let entity = &rest[1..end];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                _ => entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                    .map_or_else(
+                        || entity.strip_prefix('#').and_then(|d| d.parse().ok()),
+                        |hex| u32::from_str_radix(hex, 16).ok(),
+                    )
+                    .and_then(char::from_u32),
+            };
+            c.map(|c| (c, end))
+        });
+        if let Some((c, end)) = resolved {
+            out.push(c);
+            rest = &rest[end + 1..];
+        } else {
+            out.push('&');
+            rest = &rest[1..];
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::StatusCode;
+    use serde_json::json;
+
+    use crate::transport::{MockTransport, TransportRequest, TransportResponse};
+    use crate::{AuthArgs, AuthType, OktaArgs, SnowflakeApiBuilder};
+
+    fn okta_auth() -> AuthArgs {
+        AuthArgs {
+            account_identifier: "myorg-account".to_owned(),
+            username: "jane@example.com".to_owned(),
+            auth_type: AuthType::Okta(OktaArgs {
+                authenticator_url: "https://myorg.okta.com".to_owned(),
+                password: "okta-password".to_owned(),
+            }),
+            ..AuthArgs::for_tests()
+        }
+    }
+
+    fn push_authenticator(mock: &MockTransport, token_url: &str) {
+        mock.push_json(
+            "/session/authenticator-request",
+            &json!({
+                "code": null,
+                "message": null,
+                "success": true,
+                "data": {
+                    "tokenUrl": token_url,
+                    "ssoUrl": "https://myorg.okta.com/app/snowflake/abc123/sso/saml"
+                }
+            }),
+        );
+    }
+
+    #[tokio::test]
+    async fn logs_in_with_saml_response() {
+        let mock = MockTransport::new();
+        push_authenticator(&mock, "https://myorg.okta.com/api/v1/authn");
+        mock.push_json(
+            "/api/v1/authn",
+            &json!({ "status": "SUCCESS", "sessionToken": "one-time-token" }),
+        );
+        let saml_page = r#"<html><body><form method="post"
+            action="https&#x3a;&#x2f;&#x2f;myorg-account.snowflakecomputing.com&#x2f;fed&#x2f;login">
+            <input type="hidden" name="SAMLResponse" value="PHNhbWxwOlJlc3BvbnNlPg=="/>
+            </form></body></html>"#;
+        mock.push_response(
+            "/app/snowflake/abc123/sso/saml",
+            TransportResponse {
+                status: StatusCode::OK,
+                body: saml_page.into(),
+            },
+        );
+        mock.push_login();
+        mock.push_query_result(json!([]), json!([]));
+        let api = SnowflakeApiBuilder::new(okta_auth())
+            .with_transport(mock.clone())
+            .build()
+            .unwrap();
+
+        api.exec("SELECT 1").await.unwrap();
+
+        let requests = mock.requests();
+        let paths: Vec<_> = requests.iter().map(|r| r.url.path()).collect();
+        assert_eq!(
+            paths,
+            [
+                "/session/authenticator-request",
+                "/api/v1/authn",
+                "/app/snowflake/abc123/sso/saml",
+                "/session/v1/login-request",
+                "/queries/v1/query-request",
+            ]
+        );
+
+        let authenticator = requests[0].json_body().unwrap();
+        assert_eq!(
+            authenticator["data"]["AUTHENTICATOR"],
+            "https://myorg.okta.com"
+        );
+
+        // the password only goes to Okta
+        let credentials = requests[1].json_body().unwrap();
+        assert_eq!(credentials["password"], "okta-password");
+        assert!(requests[2..]
+            .iter()
+            .filter_map(TransportRequest::json_body)
+            .all(|body| !body.to_string().contains("okta-password")));
+
+        assert!(requests[2]
+            .url
+            .query_pairs()
+            .any(|(k, v)| k == "onetimetoken" && v == "one-time-token"));
+
+        let login = requests[3].json_body().unwrap();
+        assert_eq!(login["data"]["RAW_SAML_RESPONSE"], saml_page);
+    }
+
+    #[tokio::test]
+    async fn rejects_token_url_of_another_host() {
+        let mock = MockTransport::new();
+        push_authenticator(&mock, "https://attacker.example.com/api/v1/authn");
+        let api = SnowflakeApiBuilder::new(okta_auth())
+            .with_transport(mock.clone())
+            .build()
+            .unwrap();
+
+        let err = api.exec("SELECT 1").await.err().unwrap();
+        assert!(err.to_string().contains("attacker.example.com"));
+        assert_eq!(mock.requests().len(), 1);
+    }
+}
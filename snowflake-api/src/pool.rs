@@ -0,0 +1,140 @@
+//! Pool of sessions for concurrent query execution.
+//!
+//! A single [`SnowflakeApi`] serializes token management on one session. The pool keeps
+//! a fixed number of them, each with its own session, and hands them out one at a time:
+//!
+//! ```rust
+//! use snowflake_api::{AuthArgs, AuthType, PasswordArgs, SnowflakeApiBuilder};
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), snowflake_api::SnowflakeApiError> {
+//! let auth = AuthArgs {
+//!     account_identifier: "ACCOUNT".to_owned(),
+//!     warehouse: None,
+//!     database: None,
+//!     schema: None,
+//!     username: "USER".to_owned(),
+//!     role: None,
+//!     auth_type: AuthType::Password(PasswordArgs {
+//!         password: "password".to_owned(),
+//!         passcode: None,
+//!     }),
+//! };
+//! let pool = SnowflakeApiBuilder::new(auth).build_pool(2)?;
+//!
+//! let first = pool.acquire().await;
+//! let second = pool.acquire().await;
+//! // both sessions are taken, next caller waits until one is dropped
+//! assert!(pool.try_acquire().is_none());
+//!
+//! drop(first);
+//! assert!(pool.try_acquire().is_some());
+//! # drop(second);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::ops::Deref;
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::SnowflakeApi;
+
+/// Fixed size pool of sessions, see [`crate::SnowflakeApiBuilder::build_pool`].
+/// Cloning the pool is cheap and clones share the same sessions.
+///
+/// Sessions authenticate on their first query and start over once their tokens expire,
+/// so idle sessions don't need to be maintained.
+#[derive(Clone)]
+pub struct SessionPool {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    sessions: Mutex<Vec<SnowflakeApi>>,
+    available: Arc<Semaphore>,
+    size: usize,
+}
+
+impl SessionPool {
+    pub(crate) fn new(sessions: Vec<SnowflakeApi>) -> Self {
+        let size = sessions.len();
+        Self {
+            inner: Arc::new(Inner {
+                sessions: Mutex::new(sessions),
+                available: Arc::new(Semaphore::new(size)),
+                size,
+            }),
+        }
+    }
+
+    /// Number of sessions in the pool
+    pub fn size(&self) -> usize {
+        self.inner.size
+    }
+
+    /// Number of sessions which are not currently acquired
+    pub fn available(&self) -> usize {
+        self.inner.available.available_permits()
+    }
+
+    /// Wait for a free session, it is returned to the pool once the guard is dropped
+    pub async fn acquire(&self) -> PooledSession {
+        let permit = Arc::clone(&self.inner.available)
+            .acquire_owned()
+            .await
+            .expect("pool semaphore is never closed");
+        self.take(permit)
+    }
+
+    /// Acquire a free session without waiting, `None` if all of them are taken
+    pub fn try_acquire(&self) -> Option<PooledSession> {
+        let permit = Arc::clone(&self.inner.available).try_acquire_owned().ok()?;
+        Some(self.take(permit))
+    }
+
+    fn take(&self, permit: OwnedSemaphorePermit) -> PooledSession {
+        // permits and sessions are in lockstep, having a permit guarantees a free session
+        let api = self
+            .inner
+            .sessions
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .pop()
+            .expect("permit was acquired for a free session");
+        PooledSession {
+            api: Some(api),
+            pool: Arc::clone(&self.inner),
+            _permit: permit,
+        }
+    }
+}
+
+/// Session acquired from [`SessionPool`], dereferences into [`SnowflakeApi`]
+pub struct PooledSession {
+    api: Option<SnowflakeApi>,
+    pool: Arc<Inner>,
+    // released after the session is put back
+    _permit: OwnedSemaphorePermit,
+}
+
+impl Deref for PooledSession {
+    type Target = SnowflakeApi;
+
+    fn deref(&self) -> &Self::Target {
+        self.api.as_ref().expect("session is present until dropped")
+    }
+}
+
+impl Drop for PooledSession {
+    fn drop(&mut self) {
+        if let Some(api) = self.api.take() {
+            self.pool
+                .sessions
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .push(api);
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use crate::bindings::ToBindings;
+use crate::{ExecRequestBuilder, QueryResult, SnowflakeApi, SnowflakeApiError};
+
+/// SQL text executed repeatedly with different bindings, see [`SnowflakeApi::prepare`].
+///
+/// Snowflake doesn't prepare statements on the server, the statement is sent in full on every
+/// execution and compiled by Snowflake, which caches compiled plans on its own.
+/// This is a client-side convenience to keep the SQL in one place.
+/// Every execution is a separate request with its own sequence id.
+///
+/// ```rust
+/// # #[cfg(feature = "test-util")]
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), snowflake_api::SnowflakeApiError> {
+/// # use serde_json::json;
+/// # use snowflake_api::transport::MockTransport;
+/// # use snowflake_api::{AuthArgs, SnowflakeApiBuilder};
+/// # let mock = MockTransport::new();
+/// # mock.push_login();
+/// # mock.push_query_result(json!([]), json!([]));
+/// # mock.push_query_result(json!([]), json!([]));
+/// # let auth = AuthArgs::for_tests();
+/// # let api = SnowflakeApiBuilder::new(auth).with_transport(mock.clone()).build()?;
+/// let insert = api.prepare("INSERT INTO FRUITS (ID, NAME) VALUES (?, ?)");
+/// insert.execute(&(1, "apple")).await?;
+/// insert.execute(&(2, "banana")).await?;
+///
+/// let sent: Vec<_> = mock.requests()[1..]
+///     .iter()
+///     .map(|r| r.json_body().unwrap())
+///     .collect();
+/// assert_eq!(sent[0]["sqlText"], sent[1]["sqlText"]);
+/// assert_eq!(sent[0]["bindings"]["2"]["value"], "apple");
+/// assert_eq!(sent[1]["bindings"]["2"]["value"], "banana");
+/// assert_eq!(sent[0]["sequenceId"], 1);
+/// assert_eq!(sent[1]["sequenceId"], 2);
+/// # Ok(())
+/// # }
+/// # #[cfg(not(feature = "test-util"))]
+/// # fn main() {}
+/// ```
+pub struct PreparedStatement<'a> {
+    api: &'a SnowflakeApi,
+    sql: String,
+}
+
+impl<'a> PreparedStatement<'a> {
+    pub(crate) fn new(api: &'a SnowflakeApi, sql: &str) -> Self {
+        Self {
+            api,
+            sql: sql.to_owned(),
+        }
+    }
+
+    /// SQL text of the statement
+    pub fn sql(&self) -> &str {
+        &self.sql
+    }
+
+    /// Execute the statement with the given parameters, eg a tuple of positional values
+    pub async fn execute<B: ToBindings + ?Sized>(
+        &self,
+        params: &B,
+    ) -> Result<QueryResult, SnowflakeApiError> {
+        self.api.exec_request(self.request(params)?).await
+    }
+
+    /// Request of a single execution, to set other options before executing it
+    /// with [`SnowflakeApi::exec_request`], eg a timeout
+    pub fn request<B: ToBindings + ?Sized>(
+        &self,
+        params: &B,
+    ) -> Result<ExecRequestBuilder, SnowflakeApiError> {
+        Ok(ExecRequestBuilder::new().sql(&self.sql).bind_all(params)?)
+    }
+}
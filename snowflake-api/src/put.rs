@@ -1,10 +1,14 @@
 use std::fs::Metadata;
+use std::io::Write;
 use std::path::Path;
 use std::sync::Arc;
 
+use bytes::Bytes;
+use flate2::write::GzEncoder;
+use flate2::Compression;
 use futures::stream::FuturesUnordered;
 use futures::TryStreamExt;
-use object_store::aws::AmazonS3Builder;
+use object_store::aws::{AmazonS3, AmazonS3Builder};
 use object_store::limit::LimitStore;
 use object_store::local::LocalFileSystem;
 use object_store::ObjectStore;
@@ -13,7 +17,66 @@ use tokio::task;
 use crate::responses::{AwsPutGetStageInfo, PutGetExecResponse, PutGetStageInfo};
 use crate::SnowflakeApiError;
 
+/// Options of the `PUT` command, see [`crate::SnowflakeApi::put_file`]
+#[derive(Debug, Clone)]
+pub struct PutOptions {
+    /// Gzip files which are not compressed yet, `.gz` is appended to their names
+    pub auto_compress: bool,
+    /// Replace files with the same name in the stage, otherwise they are skipped
+    pub overwrite: bool,
+}
+
+impl Default for PutOptions {
+    /// Same as Snowflake defaults
+    fn default() -> Self {
+        Self {
+            auto_compress: true,
+            overwrite: false,
+        }
+    }
+}
+
+/// Upload behaviour requested in the `PUT` command and returned back by Snowflake
+#[derive(Debug, Clone)]
+struct UploadOptions {
+    auto_compress: bool,
+    overwrite: bool,
+    // `auto_detect`, `none` or the compression files are declared to have already
+    source_compression: String,
+}
+
+/// Extensions of compressed files, which `AUTO_COMPRESS` leaves as-is
+const COMPRESSED_EXTENSIONS: [&str; 6] = ["gz", "bz2", "br", "zst", "deflate", "raw_deflate"];
+
+impl UploadOptions {
+    fn should_compress(&self, filename: &str) -> bool {
+        if !self.auto_compress {
+            return false;
+        }
+
+        match self.source_compression.to_lowercase().as_str() {
+            "none" => true,
+            "auto_detect" => !Path::new(filename)
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| {
+                    COMPRESSED_EXTENSIONS
+                        .iter()
+                        .any(|c| e.eq_ignore_ascii_case(c))
+                }),
+            // files are declared to be compressed with the given algorithm
+            _ => false,
+        }
+    }
+}
+
 pub async fn put(resp: PutGetExecResponse) -> Result<(), SnowflakeApiError> {
+    let options = UploadOptions {
+        auto_compress: resp.data.auto_compress,
+        overwrite: resp.data.overwrite,
+        source_compression: resp.data.source_compression,
+    };
+
     match resp.data.stage_info {
         PutGetStageInfo::Aws(info) => {
             put_to_s3(
@@ -21,6 +84,7 @@ pub async fn put(resp: PutGetExecResponse) -> Result<(), SnowflakeApiError> {
                 info,
                 resp.data.parallel,
                 resp.data.threshold,
+                options,
             )
             .await
         }
@@ -38,7 +102,25 @@ async fn put_to_s3(
     info: AwsPutGetStageInfo,
     max_parallel_uploads: usize,
     max_file_size_threshold: i64,
+    options: UploadOptions,
 ) -> Result<(), SnowflakeApiError> {
+    let (s3, bucket_path) = s3_store(info)?;
+    let bucket_path = bucket_path.as_str();
+
+    let files = list_files(src_locations, max_file_size_threshold).await?;
+
+    for src_path in files.large_files {
+        upload_file(&s3, &src_path, bucket_path, &options).await?;
+    }
+
+    let limit_store = LimitStore::new(s3, max_parallel_uploads);
+    put_files_par(files.small_files, bucket_path, limit_store, options).await?;
+
+    Ok(())
+}
+
+/// Store of the stage with the path of stage files inside the bucket
+pub(crate) fn s3_store(info: AwsPutGetStageInfo) -> Result<(AmazonS3, String), SnowflakeApiError> {
     // These constants are based on the snowflake website
     let (bucket_name, bucket_path) = info
         .location
@@ -53,16 +135,7 @@ async fn put_to_s3(
         .with_token(info.creds.aws_token)
         .build()?;
 
-    let files = list_files(src_locations, max_file_size_threshold).await?;
-
-    for src_path in files.large_files {
-        put_file(&s3, &src_path, bucket_path).await?;
-    }
-
-    let limit_store = LimitStore::new(s3, max_parallel_uploads);
-    put_files_par(files.small_files, bucket_path, limit_store).await?;
-
-    Ok(())
+    Ok((s3, bucket_path.to_owned()))
 }
 
 /// Sorts upload files by whether they are larger or smaller than the threshold
@@ -127,26 +200,48 @@ async fn fetch_metadata(paths: Vec<String>) -> Result<Vec<PathMeta>, SnowflakeAp
     metadata.try_collect().await
 }
 
-async fn put_file<T: ObjectStore>(
+async fn upload_file<T: ObjectStore>(
     store: &T,
     src_path: &str,
     bucket_path: &str,
+    options: &UploadOptions,
 ) -> Result<(), SnowflakeApiError> {
     let filename = Path::new(&src_path)
         .file_name()
         .and_then(|f| f.to_str())
         .ok_or(SnowflakeApiError::InvalidLocalPath(src_path.to_owned()))?;
 
-    let dest_path = format!("{bucket_path}{filename}");
+    let compress = options.should_compress(filename);
+    let dest_path = if compress {
+        format!("{bucket_path}{filename}.gz")
+    } else {
+        format!("{bucket_path}{filename}")
+    };
     let dest_path = object_store::path::Path::parse(dest_path)?;
+
+    if !options.overwrite && store.head(&dest_path).await.is_ok() {
+        log::info!("Skipping upload of {src_path}, {dest_path} already exists");
+        return Ok(());
+    }
+
     let src_path = object_store::path::Path::parse(src_path)?;
     let fs = LocalFileSystem::new().get(&src_path).await?;
+    let mut payload = fs.bytes().await?;
+    if compress {
+        payload = task::spawn_blocking(move || gzip(&payload)).await??;
+    }
 
-    store.put(&dest_path, fs.bytes().await?.into()).await?;
+    store.put(&dest_path, payload.into()).await?;
 
     Ok::<(), SnowflakeApiError>(())
 }
 
+fn gzip(bytes: &[u8]) -> Result<Bytes, SnowflakeApiError> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(bytes)?;
+    Ok(Bytes::from(encoder.finish()?))
+}
+
 /// This function uploads files in parallel, useful for files below the threshold
 /// One potential issue is that file size could be changed between when the file is
 /// checked and when it is uploaded
@@ -154,13 +249,18 @@ async fn put_files_par<T: ObjectStore>(
     files: Vec<String>,
     bucket_path: &str,
     limit_store: LimitStore<T>,
+    options: UploadOptions,
 ) -> Result<(), SnowflakeApiError> {
     let limit_store = Arc::new(limit_store);
+    let options = Arc::new(options);
     let mut tasks = task::JoinSet::new();
     for src_path in files {
         let bucket_path = bucket_path.to_owned();
         let limit_store = Arc::clone(&limit_store);
-        tasks.spawn(async move { put_file(limit_store.as_ref(), &src_path, &bucket_path).await });
+        let options = Arc::clone(&options);
+        tasks.spawn(async move {
+            upload_file(limit_store.as_ref(), &src_path, &bucket_path, &options).await
+        });
     }
     while let Some(result) = tasks.join_next().await {
         result??;
@@ -1,5 +1,11 @@
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
 use serde::Serialize;
 
+use crate::bindings::{self, BindingError, ToBindings, ToSql};
+use crate::responses::SnowflakeType;
+
 #[derive(Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct ExecRequest {
@@ -7,6 +13,323 @@ pub struct ExecRequest {
     pub async_exec: bool,
     pub sequence_id: u64,
     pub is_internal: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bindings: Option<Bindings>,
+    /// Statement level session parameters, eg `MULTI_STATEMENT_COUNT`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<HashMap<String, serde_json::Value>>,
+}
+
+impl ExecRequest {
+    /// Synchronous request without bindings, `sequence_id` is assigned when it's sent
+    pub fn new(sql_text: &str) -> Self {
+        Self {
+            sql_text: sql_text.to_string(),
+            async_exec: false,
+            sequence_id: 0,
+            is_internal: false,
+            bindings: None,
+            parameters: None,
+        }
+    }
+}
+
+/// Builder of a query request with parameter bindings, see [`crate::SnowflakeApi::exec_request`].
+/// Sequence id is assigned from the session counter when the request is sent.
+///
+/// ```rust
+/// use snowflake_api::{BindingValue, ExecRequestBuilder, SnowflakeType};
+///
+/// let request = ExecRequestBuilder::new()
+///     .sql("SELECT * FROM users WHERE id = ? AND name = ?")
+///     .bind("1", 42)
+///     .unwrap()
+///     .bind("2", "john")
+///     .unwrap();
+///
+/// let bindings = request.bindings();
+/// assert_eq!(bindings.len(), 2);
+/// assert_eq!(bindings["1"].type_, Some(SnowflakeType::Fixed));
+/// assert_eq!(
+///     bindings["2"].value,
+///     Some(BindingValue::SingleBind("john".to_owned()))
+/// );
+/// ```
+#[must_use]
+#[derive(Debug, Default)]
+pub struct ExecRequestBuilder {
+    sql_text: String,
+    async_exec: bool,
+    bindings: Bindings,
+    parameters: HashMap<String, serde_json::Value>,
+    timeout: Option<Duration>,
+    is_internal: bool,
+}
+
+impl ExecRequestBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn sql(mut self, sql_text: &str) -> Self {
+        self.sql_text = sql_text.to_string();
+        self
+    }
+
+    /// `INSERT` of all the rows in a single request, each column is bound as an array of its values.
+    /// Table and column names are used as-is, quote them if needed.
+    ///
+    /// Column type is taken from its first non-null value, NULLs in other rows keep that type.
+    /// Values of different types within a column are rejected.
+    ///
+    /// ```rust
+    /// use snowflake_api::bindings::ToSql;
+    /// use snowflake_api::{BindingValue, ExecRequestBuilder, SnowflakeType};
+    ///
+    /// let rows = [
+    ///     [&1 as &dyn ToSql, &"apple"],
+    ///     [&2, &None::<&str>],
+    ///     [&3, &"cherry"],
+    /// ];
+    /// let request = ExecRequestBuilder::insert_rows("FRUITS", &["ID", "NAME"], &rows).unwrap();
+    ///
+    /// let bindings = request.bindings();
+    /// assert_eq!(bindings.len(), 2);
+    /// assert_eq!(bindings["1"].type_, Some(SnowflakeType::Fixed));
+    /// assert_eq!(bindings["2"].type_, Some(SnowflakeType::Text));
+    /// assert_eq!(
+    ///     bindings["2"].value,
+    ///     Some(BindingValue::MultiBind(vec![
+    ///         Some("apple".to_owned()),
+    ///         None,
+    ///         Some("cherry".to_owned())
+    ///     ]))
+    /// );
+    /// ```
+    pub fn insert_rows<'a, R: AsRef<[&'a dyn ToSql]>>(
+        table: &str,
+        columns: &[&str],
+        rows: &[R],
+    ) -> Result<Self, BindingError> {
+        if let Some((i, row)) = rows
+            .iter()
+            .enumerate()
+            .find(|(_, row)| row.as_ref().len() != columns.len())
+        {
+            return Err(BindingError::RowLengthMismatch(
+                i,
+                row.as_ref().len(),
+                columns.len(),
+            ));
+        }
+
+        let placeholders = vec!["?"; columns.len()].join(", ");
+        let mut request = Self::new().sql(&format!(
+            "INSERT INTO {table} ({}) VALUES ({placeholders})",
+            columns.join(", ")
+        ));
+
+        for (i, column) in columns.iter().enumerate() {
+            let mut type_ = None;
+            let mut fmt = "";
+            let mut values = Vec::with_capacity(rows.len());
+            for value in rows.iter().map(|row| row.as_ref()[i]) {
+                match (type_, value.sql_type()) {
+                    (_, SnowflakeType::UnknownNull) => {}
+                    (None, ty) => type_ = Some(ty),
+                    (Some(expected), ty) if expected != ty => {
+                        return Err(BindingError::MixedColumnTypes(
+                            (*column).to_owned(),
+                            expected,
+                            ty,
+                        ));
+                    }
+                    _ => {}
+                }
+                if fmt.is_empty() {
+                    fmt = value.encode_format();
+                }
+                values.push(bindings::to_text(value).map_err(|e| e.for_parameter(*column))?);
+            }
+
+            request.bindings.insert(
+                (i + 1).to_string(),
+                ParameterBinding {
+                    type_: Some(type_.unwrap_or(SnowflakeType::UnknownNull)),
+                    value: Some(BindingValue::MultiBind(values)),
+                    fmt: (!fmt.is_empty()).then(|| fmt.to_owned()),
+                },
+            );
+        }
+        Ok(request)
+    }
+
+    /// Bind value to the parameter with given position (`"1"`, `"2"`, ...) or name,
+    /// binding the same parameter again replaces the value
+    pub fn bind<T: ToSql>(mut self, name: &str, value: T) -> Result<Self, BindingError> {
+        let binding = ParameterBinding::from_value(value).map_err(|e| e.for_parameter(name))?;
+        self.bindings.insert(name.to_string(), binding);
+        Ok(self)
+    }
+
+    /// Bind a whole set of parameters, eg a tuple or a struct deriving [`ToBindings`],
+    /// replacing values of the same parameters bound before
+    pub fn bind_all<B: ToBindings + ?Sized>(mut self, params: &B) -> Result<Self, BindingError> {
+        self.bindings.extend(params.to_bindings()?);
+        Ok(self)
+    }
+
+    /// Submit query without waiting for its result
+    pub fn async_exec(mut self, async_exec: bool) -> Self {
+        self.async_exec = async_exec;
+        self
+    }
+
+    /// Mark the statement as issued by the client on its own rather than by the user,
+    /// as the `USE` statements of [`crate::SnowflakeApi::use_warehouse`] are.
+    /// Snowflake uses the flag to tell such statements apart from user queries
+    /// and may leave them out of the query history shown to users.
+    /// Internal statements are executed, and billed, as any other.
+    ///
+    /// ```rust
+    /// use snowflake_api::ExecRequestBuilder;
+    ///
+    /// let request = ExecRequestBuilder::new().sql("USE WAREHOUSE WH").internal(true);
+    /// assert_eq!(request.render().unwrap()["isInternal"], true);
+    ///
+    /// let request = ExecRequestBuilder::new().sql("SELECT 1");
+    /// assert_eq!(request.render().unwrap()["isInternal"], false);
+    /// ```
+    pub fn internal(mut self, is_internal: bool) -> Self {
+        self.is_internal = is_internal;
+        self
+    }
+
+    /// Limit execution time of the query, both on the server with `STATEMENT_TIMEOUT_IN_SECONDS`,
+    /// rounded up to whole seconds, and on the client, which cancels the query once it fires.
+    /// Whichever fires first fails the query.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        // zero disables the timeout, so sub-second timeouts are rounded up
+        let seconds = timeout.as_secs() + u64::from(timeout.subsec_nanos() > 0);
+        self.parameters.insert(
+            "STATEMENT_TIMEOUT_IN_SECONDS".to_owned(),
+            serde_json::Value::from(seconds.max(1)),
+        );
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Tag the statement with `QUERY_TAG`, eg to find it in `QUERY_HISTORY`.
+    /// The tag is a statement parameter and doesn't apply to other statements of the session,
+    /// use `ALTER SESSION SET QUERY_TAG = '...'` to tag all of them.
+    ///
+    /// ```rust
+    /// use snowflake_api::ExecRequestBuilder;
+    ///
+    /// let tagged = ExecRequestBuilder::new()
+    ///     .sql("SELECT 1")
+    ///     .query_tag("nightly-report");
+    /// assert_eq!(tagged.parameters()["QUERY_TAG"], "nightly-report");
+    ///
+    /// let untagged = ExecRequestBuilder::new().sql("SELECT 2");
+    /// assert!(untagged.parameters().is_empty());
+    /// ```
+    pub fn query_tag(mut self, tag: &str) -> Self {
+        self.parameters
+            .insert("QUERY_TAG".to_owned(), serde_json::Value::from(tag));
+        self
+    }
+
+    /// Set a session parameter for this statement only, eg `DATE_INPUT_FORMAT`.
+    /// Parameters set by other methods, eg [`ExecRequestBuilder::query_tag`], are replaced.
+    pub fn parameter(mut self, name: &str, value: impl Into<serde_json::Value>) -> Self {
+        self.parameters.insert(name.to_owned(), value.into());
+        self
+    }
+
+    /// Bindings accumulated so far
+    pub fn bindings(&self) -> &Bindings {
+        &self.bindings
+    }
+
+    /// Statement level parameters set so far, eg `QUERY_TAG`
+    pub fn parameters(&self) -> &HashMap<String, serde_json::Value> {
+        &self.parameters
+    }
+
+    pub(crate) fn client_timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// JSON body of the request as it would be sent, without executing it,
+    /// eg to check the type and format of each binding.
+    /// `sequenceId` is `0`, the actual one is assigned by the session when the request is sent.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    /// use snowflake_api::ExecRequestBuilder;
+    ///
+    /// let request = ExecRequestBuilder::new()
+    ///     .sql("SELECT * FROM users WHERE id = ? AND name = ?")
+    ///     .bind("1", 42)
+    ///     .unwrap()
+    ///     .bind("2", "john")
+    ///     .unwrap();
+    ///
+    /// assert_eq!(
+    ///     request.render().unwrap(),
+    ///     json!({
+    ///         "sqlText": "SELECT * FROM users WHERE id = ? AND name = ?",
+    ///         "asyncExec": false,
+    ///         "sequenceId": 0,
+    ///         "isInternal": false,
+    ///         "bindings": {
+    ///             "1": { "type": "FIXED", "value": "42" },
+    ///             "2": { "type": "TEXT", "value": "john" }
+    ///         }
+    ///     })
+    /// );
+    /// ```
+    pub fn render(&self) -> Result<serde_json::Value, serde_json::Error> {
+        serde_json::to_value(ExecRequest {
+            async_exec: self.async_exec,
+            is_internal: self.is_internal,
+            bindings: (!self.bindings.is_empty()).then(|| self.bindings.clone()),
+            parameters: (!self.parameters.is_empty()).then(|| self.parameters.clone()),
+            ..ExecRequest::new(&self.sql_text)
+        })
+    }
+
+    pub(crate) fn build(self) -> ExecRequest {
+        ExecRequest {
+            async_exec: self.async_exec,
+            is_internal: self.is_internal,
+            bindings: (!self.bindings.is_empty()).then_some(self.bindings),
+            parameters: (!self.parameters.is_empty()).then_some(self.parameters),
+            ..ExecRequest::new(&self.sql_text)
+        }
+    }
+}
+
+/// Parameter bindings keyed by their position (`"1"`, `"2"`, ...) or name
+pub type Bindings = BTreeMap<String, ParameterBinding>;
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ParameterBinding {
+    #[serde(rename = "type")]
+    pub type_: Option<SnowflakeType>,
+    // `None` is sent as SQL NULL
+    pub value: Option<BindingValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fmt: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum BindingValue {
+    SingleBind(String),
+    // array binding, eg for batch inserts, `None` elements are sent as NULL
+    MultiBind(Vec<Option<String>>),
 }
 
 #[derive(Serialize, Debug)]
@@ -15,8 +338,18 @@ pub struct LoginRequest<T> {
 }
 
 pub type PasswordLoginRequest = LoginRequest<PasswordRequestData>;
+pub type OAuthLoginRequest = LoginRequest<OAuthRequestData>;
+pub type ProgrammaticAccessTokenLoginRequest = LoginRequest<ProgrammaticAccessTokenRequestData>;
+pub type ExternalBrowserLoginRequest = LoginRequest<ExternalBrowserRequestData>;
+pub type AuthenticatorRequest = LoginRequest<AuthenticatorRequestData>;
+pub type OktaAuthenticatorRequest = LoginRequest<OktaAuthenticatorRequestData>;
+pub type OktaLoginRequest = LoginRequest<OktaRequestData>;
 #[cfg(feature = "cert-auth")]
 pub type CertLoginRequest = LoginRequest<CertRequestData>;
+#[cfg(feature = "cert-auth")]
+pub type KeyPairLoginRequest = CertLoginRequest;
+#[cfg(feature = "cert-auth")]
+pub type KeyPairRequestData = CertRequestData;
 
 #[derive(Serialize, Debug)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -34,6 +367,9 @@ pub struct LoginRequestCommon {
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub struct SessionParameters {
     pub client_validate_default_parameters: bool,
+    /// Other parameters, eg `TIMEZONE`, keyed by their upper case names
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_json::Value>,
 }
 
 #[derive(Serialize, Debug)]
@@ -51,10 +387,19 @@ pub struct PasswordRequestData {
     #[serde(flatten)]
     pub login_request_common: LoginRequestCommon,
     pub password: String,
+    /// MFA passcode, eg from the DUO app
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub passcode: Option<String>,
+    /// `passcode` when the passcode is given, otherwise DUO sends a push notification
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ext_authn_duo_method: Option<String>,
 }
 
+/// Key-pair authentication, `token` is a JWT signed with user's private key
+/// and `authenticator` is `SNOWFLAKE_JWT`
 #[derive(Serialize, Debug)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[cfg_attr(not(feature = "cert-auth"), allow(dead_code))]
 pub struct CertRequestData {
     #[serde(flatten)]
     pub login_request_common: LoginRequestCommon,
@@ -62,6 +407,86 @@ pub struct CertRequestData {
     pub token: String,
 }
 
+/// `token` is an OAuth access token issued by the external provider,
+/// `authenticator` is `OAUTH`
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct OAuthRequestData {
+    #[serde(flatten)]
+    pub login_request_common: LoginRequestCommon,
+    pub authenticator: String,
+    pub token: String,
+}
+
+/// `token` is a programmatic access token of the user, `authenticator` is `PROGRAMMATIC_ACCESS_TOKEN`.
+/// The token is sent in the same `TOKEN` field as OAuth tokens, while the password login
+/// would send it as `PASSWORD`.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct ProgrammaticAccessTokenRequestData {
+    #[serde(flatten)]
+    pub login_request_common: LoginRequestCommon,
+    pub authenticator: String,
+    pub token: String,
+}
+
+/// Request for the SSO URL and the proof key, `authenticator` is `EXTERNALBROWSER`.
+/// Identity provider redirects the browser to `localhost` on `browser_mode_redirect_port`.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct AuthenticatorRequestData {
+    #[serde(flatten)]
+    pub login_request_common: LoginRequestCommon,
+    pub authenticator: String,
+    pub browser_mode_redirect_port: String,
+}
+
+/// `token` is the one received on the browser redirect, `proof_key` is returned
+/// along with the SSO URL, `authenticator` is `EXTERNALBROWSER`
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct ExternalBrowserRequestData {
+    #[serde(flatten)]
+    pub login_request_common: LoginRequestCommon,
+    pub authenticator: String,
+    pub token: String,
+    pub proof_key: String,
+}
+
+/// Request for the token and SSO URLs of Okta, `authenticator` is the Okta URL of the organization
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct OktaAuthenticatorRequestData {
+    #[serde(flatten)]
+    pub login_request_common: LoginRequestCommon,
+    pub authenticator: String,
+}
+
+/// Credentials sent to the Okta token URL, not to Snowflake
+#[derive(Serialize, Debug)]
+pub struct OktaTokenRequest {
+    pub username: String,
+    pub password: String,
+}
+
+/// `raw_saml_response` is the whole HTML page returned by the Okta SSO URL
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct OktaRequestData {
+    #[serde(flatten)]
+    pub login_request_common: LoginRequestCommon,
+    pub raw_saml_response: String,
+}
+
+/// `request_id` is the one the query was submitted with,
+/// `sql_text` is informational and empty when the statement isn't known
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AbortRequest {
+    pub sql_text: String,
+    pub request_id: String,
+}
+
 #[derive(Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct RenewSessionRequest {
@@ -1,6 +1,9 @@
 use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
 #[allow(clippy::large_enum_variant)]
 #[derive(Deserialize, Debug)]
@@ -8,6 +11,7 @@ use serde::Deserialize;
 pub enum ExecResponse {
     Query(QueryExecResponse),
     PutGet(PutGetExecResponse),
+    Async(AsyncExecResponse),
     Error(ExecErrorResponse),
 }
 
@@ -36,12 +40,16 @@ pub struct BaseRestResponse<D> {
 pub type PutGetExecResponse = BaseRestResponse<PutGetResponseData>;
 pub type QueryExecResponse = BaseRestResponse<QueryExecResponseData>;
 pub type ExecErrorResponse = BaseRestResponse<ExecErrorResponseData>;
+pub type AsyncExecResponse = BaseRestResponse<AsyncExecResponseData>;
+pub type QueryStatusResponse = BaseRestResponse<QueryStatusResponseData>;
 pub type AuthErrorResponse = BaseRestResponse<AuthErrorResponseData>;
 pub type AuthenticatorResponse = BaseRestResponse<AuthenticatorResponseData>;
 pub type LoginResponse = BaseRestResponse<LoginResponseData>;
 pub type RenewSessionResponse = BaseRestResponse<RenewSessionResponseData>;
 // Data should be always `null` on successful close session response
 pub type CloseSessionResponse = BaseRestResponse<Option<()>>;
+pub type AbortQueryResponse = BaseRestResponse<Option<serde_json::Value>>;
+pub type HeartbeatResponse = BaseRestResponse<Option<serde_json::Value>>;
 
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -55,8 +63,8 @@ pub struct ExecErrorResponseData {
     pub pos: Option<i64>,
 
     // fixme: only valid for exec query response error? present in any exec query response?
-    pub query_id: String,
-    pub sql_state: String,
+    pub query_id: Option<String>,
+    pub sql_state: Option<String>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -106,11 +114,25 @@ pub struct SessionInfo {
 // FIXME: dead_code
 #[allow(dead_code)]
 pub struct AuthenticatorResponseData {
-    pub token_url: String,
+    // only returned for native Okta authentication
+    pub token_url: Option<String>,
     pub sso_url: String,
+    // not returned for native Okta authentication
+    #[serde(default)]
     pub proof_key: String,
 }
 
+/// Response of the Okta token URL, one of the tokens is returned on success
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OktaTokenResponse {
+    pub session_token: Option<String>,
+    pub cookie_token: Option<String>,
+    /// eg `MFA_REQUIRED`, which isn't supported by the native flow
+    pub status: Option<String>,
+    pub error_summary: Option<String>,
+}
+
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 // FIXME: dead_code
@@ -159,8 +181,103 @@ pub struct QueryExecResponseData {
     pub get_result_url: Option<String>,
     // multi-statement response, comma-separated
     pub result_ids: Option<String>,
+    // `json` or `arrow`, depending on `QUERY_RESULT_FORMAT` and the statement
+    pub query_result_format: Option<QueryResultFormat>,
     // `progressDesc`, and `queryAbortAfterSecs` are not used but exist in .NET
-    // `sendResultTime`, `queryResultFormat`, `queryContext` also exist
+    // `sendResultTime`, `queryContext` also exist
+}
+
+impl QueryExecResponseData {
+    /// Format of the rowset, inferred from the rowset which is present when the server doesn't report it
+    pub fn result_format(&self) -> Option<QueryResultFormat> {
+        self.query_result_format.or(if self.rowset.is_some() {
+            Some(QueryResultFormat::Json)
+        } else if self.rowset_base64.is_some() {
+            Some(QueryResultFormat::Arrow)
+        } else {
+            None
+        })
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum QueryResultFormat {
+    /// Rows are in `rowset`, an array of arrays of strings
+    Json,
+    /// Rows are in `rowsetBase64` and chunks, as Arrow IPC streams
+    Arrow,
+    #[serde(other)]
+    Unknown,
+}
+
+/// Returned for queries submitted with `asyncExec`,
+/// or for synchronous queries which didn't finish within the request timeout
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AsyncExecResponseData {
+    pub query_id: String,
+    pub get_result_url: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct QueryStatusResponseData {
+    #[serde(default)]
+    pub queries: Vec<QueryStatusEntry>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryStatusEntry {
+    pub id: String,
+    pub status: QueryStatus,
+    pub error_code: Option<String>,
+    pub error_message: Option<String>,
+}
+
+/// Execution status of the query, as reported by the monitoring endpoint
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum QueryStatus {
+    Running,
+    Aborting,
+    Success,
+    FailedWithError,
+    Aborted,
+    Queued,
+    FailedWithIncident,
+    Disconnected,
+    ResumingWarehouse,
+    QueuedReparingWarehouse,
+    Restarted,
+    Blocked,
+    NoData,
+    #[serde(other)]
+    Unknown,
+}
+
+impl QueryStatus {
+    /// Query didn't finish yet, its result is not available.
+    /// `NoData` is neither running nor finished, the query might not exist at all.
+    pub fn is_running(self) -> bool {
+        matches!(
+            self,
+            Self::Running | Self::Queued | Self::ResumingWarehouse | Self::QueuedReparingWarehouse
+        )
+    }
+
+    /// Query finished without producing a result
+    pub fn is_error(self) -> bool {
+        matches!(
+            self,
+            Self::Aborting
+                | Self::FailedWithError
+                | Self::Aborted
+                | Self::FailedWithIncident
+                | Self::Disconnected
+                | Self::Blocked
+        )
+    }
 }
 
 #[derive(Deserialize, Debug)]
@@ -178,8 +295,59 @@ pub struct ExecResponseRowType {
 }
 
 // fixme: is it good idea to keep this as an enum if more types could be added in future?
-#[derive(Deserialize, Debug)]
-#[serde(rename_all = "snake_case")]
+// responses use lowercase type names, while bindings expect them uppercase
+/// Logical type of a column or a binding
+///
+/// ```rust
+/// use snowflake_api::SnowflakeType;
+///
+/// let ty: SnowflakeType = serde_json::from_str(r#""geography""#).unwrap();
+/// assert_eq!(ty, SnowflakeType::Geography);
+/// assert_eq!(serde_json::to_string(&ty).unwrap(), r#""GEOGRAPHY""#);
+///
+/// let ty: SnowflakeType = serde_json::from_str(r#""geometry""#).unwrap();
+/// assert_eq!(ty, SnowflakeType::Geometry);
+/// assert_eq!(serde_json::to_string(&ty).unwrap(), r#""GEOMETRY""#);
+/// ```
+///
+/// Outside of serde types are printed in the upper case form of bindings,
+/// and parsed from either form:
+///
+/// ```rust
+/// use snowflake_api::{SnowflakeType, UnknownSnowflakeType};
+///
+/// assert_eq!(SnowflakeType::TimestampNtz.to_string(), "TIMESTAMP_NTZ");
+/// assert_eq!("fixed".parse::<SnowflakeType>().unwrap(), SnowflakeType::Fixed);
+///
+/// for ty in [
+///     SnowflakeType::Fixed,
+///     SnowflakeType::Real,
+///     SnowflakeType::Text,
+///     SnowflakeType::Date,
+///     SnowflakeType::Variant,
+///     SnowflakeType::TimestampLtz,
+///     SnowflakeType::TimestampNtz,
+///     SnowflakeType::TimestampTz,
+///     SnowflakeType::Object,
+///     SnowflakeType::Binary,
+///     SnowflakeType::Time,
+///     SnowflakeType::Boolean,
+///     SnowflakeType::Array,
+///     SnowflakeType::Geography,
+///     SnowflakeType::Geometry,
+///     SnowflakeType::UnknownNull,
+/// ] {
+///     assert_eq!(ty.to_string().parse::<SnowflakeType>().unwrap(), ty);
+///     assert_eq!(ty.to_string(), serde_json::to_value(ty).unwrap());
+/// }
+///
+/// assert!(matches!(
+///     "VARCHAR".parse::<SnowflakeType>(),
+///     Err(UnknownSnowflakeType(name)) if name == "VARCHAR"
+/// ));
+/// ```
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all(deserialize = "snake_case", serialize = "SCREAMING_SNAKE_CASE"))]
 pub enum SnowflakeType {
     Fixed,
     Real,
@@ -194,6 +362,73 @@ pub enum SnowflakeType {
     Time,
     Boolean,
     Array,
+    Geography,
+    Geometry,
+    // NULL binding which carries no type information
+    #[serde(rename = "ANY")]
+    UnknownNull,
+}
+
+impl SnowflakeType {
+    /// Name of the type as it's sent in bindings
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Fixed => "FIXED",
+            Self::Real => "REAL",
+            Self::Text => "TEXT",
+            Self::Date => "DATE",
+            Self::Variant => "VARIANT",
+            Self::TimestampLtz => "TIMESTAMP_LTZ",
+            Self::TimestampNtz => "TIMESTAMP_NTZ",
+            Self::TimestampTz => "TIMESTAMP_TZ",
+            Self::Object => "OBJECT",
+            Self::Binary => "BINARY",
+            Self::Time => "TIME",
+            Self::Boolean => "BOOLEAN",
+            Self::Array => "ARRAY",
+            Self::Geography => "GEOGRAPHY",
+            Self::Geometry => "GEOMETRY",
+            Self::UnknownNull => "ANY",
+        }
+    }
+}
+
+impl Display for SnowflakeType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Error, Debug)]
+#[error("Unknown Snowflake type `{0}`")]
+pub struct UnknownSnowflakeType(pub String);
+
+/// Names are matched case-insensitively, as responses use lower case names
+impl FromStr for SnowflakeType {
+    type Err = UnknownSnowflakeType;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let ty = match s.to_ascii_uppercase().as_str() {
+            "FIXED" => Self::Fixed,
+            "REAL" => Self::Real,
+            "TEXT" => Self::Text,
+            "DATE" => Self::Date,
+            "VARIANT" => Self::Variant,
+            "TIMESTAMP_LTZ" => Self::TimestampLtz,
+            "TIMESTAMP_NTZ" => Self::TimestampNtz,
+            "TIMESTAMP_TZ" => Self::TimestampTz,
+            "OBJECT" => Self::Object,
+            "BINARY" => Self::Binary,
+            "TIME" => Self::Time,
+            "BOOLEAN" => Self::Boolean,
+            "ARRAY" => Self::Array,
+            "GEOGRAPHY" => Self::Geography,
+            "GEOMETRY" => Self::Geometry,
+            "ANY" => Self::UnknownNull,
+            _ => return Err(UnknownSnowflakeType(s.to_owned())),
+        };
+        Ok(ty)
+    }
 }
 
 #[derive(Deserialize, Debug)]
@@ -0,0 +1,169 @@
+//! Typed access to rows of JSON results.
+//!
+//! ```rust
+//! use serde_json::json;
+//! use snowflake_api::from_sql::FromSqlError;
+//! use snowflake_api::{FieldSchema, JsonResult, SnowflakeType};
+//!
+//! let field = |name: &str, type_| FieldSchema {
+//!     name: name.to_owned(),
+//!     type_,
+//!     scale: None,
+//!     precision: None,
+//!     length: None,
+//!     nullable: true,
+//! };
+//! let result = JsonResult {
+//!     value: json!([["1", "apple"], ["2", null]]),
+//!     schema: vec![field("ID", SnowflakeType::Fixed), field("NAME", SnowflakeType::Text)],
+//! };
+//!
+//! let rows: Vec<_> = result.rows().collect();
+//! assert_eq!(rows[0].get::<i64>(0).unwrap(), 1);
+//! assert_eq!(rows[0].get_by_name::<String>("NAME").unwrap(), "apple");
+//! assert_eq!(rows[1].get_by_name::<Option<String>>("name").unwrap(), None);
+//!
+//! assert!(matches!(rows[0].get::<i64>(2), Err(FromSqlError::OutOfBounds(2, 2))));
+//! assert!(matches!(
+//!     rows[0].get::<i64>(1),
+//!     Err(FromSqlError::WrongType(SnowflakeType::Text, _))
+//! ));
+//! ```
+//!
+//! Values are converted best-effort, eg any column can be read as `String`.
+//! [`JsonResult::strict_rows`] instead rejects types which don't match the column type,
+//! to catch schema changes early:
+//!
+//! ```rust
+//! # use serde_json::json;
+//! # use snowflake_api::from_sql::FromSqlError;
+//! # use snowflake_api::{FieldSchema, JsonResult, SnowflakeType};
+//! #
+//! # let field = |name: &str, type_| FieldSchema {
+//! #     name: name.to_owned(),
+//! #     type_,
+//! #     scale: None,
+//! #     precision: None,
+//! #     length: None,
+//! #     nullable: true,
+//! # };
+//! let result = JsonResult {
+//!     value: json!([["1", "apple"]]),
+//!     schema: vec![field("ID", SnowflakeType::Fixed), field("NAME", SnowflakeType::Text)],
+//! };
+//!
+//! let lenient = result.rows().next().unwrap();
+//! assert_eq!(lenient.get::<String>(0).unwrap(), "1");
+//!
+//! let strict = result.strict_rows().next().unwrap();
+//! assert!(matches!(
+//!     strict.get::<String>(0),
+//!     Err(FromSqlError::WrongType(SnowflakeType::Fixed, "alloc::string::String"))
+//! ));
+//! assert!(matches!(
+//!     strict.get::<Option<String>>(0),
+//!     Err(FromSqlError::WrongType(SnowflakeType::Fixed, _))
+//! ));
+//! assert_eq!(strict.get::<i64>(0).unwrap(), 1);
+//! assert_eq!(strict.get_by_name::<String>("NAME").unwrap(), "apple");
+//! ```
+
+use std::borrow::Cow;
+
+use serde_json::Value;
+
+use crate::from_sql::{FromSql, FromSqlError};
+use crate::{FieldSchema, JsonResult};
+
+/// Single row of the [`JsonResult`], values are converted using column types and scales
+pub struct Row<'a> {
+    values: &'a [Value],
+    schema: &'a [FieldSchema],
+    strict: bool,
+}
+
+impl<'a> Row<'a> {
+    /// Number of columns in the row
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Columns of the row, in the same order as values
+    pub fn columns(&self) -> &'a [FieldSchema] {
+        self.schema
+    }
+
+    /// Whether only types accepting the column type can be read, see [`JsonResult::strict_rows`]
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Convert value of the column at the given position
+    pub fn get<T: FromSql>(&self, index: usize) -> Result<T, FromSqlError> {
+        let (Some(value), Some(field)) = (self.values.get(index), self.schema.get(index)) else {
+            return Err(FromSqlError::OutOfBounds(index, self.len()));
+        };
+        if self.strict && !T::accepts(&field.type_) {
+            return Err(FromSqlError::WrongType(
+                field.type_,
+                std::any::type_name::<T>(),
+            ));
+        }
+
+        let raw = match value {
+            Value::Null => None,
+            Value::String(s) => Some(Cow::Borrowed(s.as_str())),
+            other => Some(Cow::Owned(other.to_string())),
+        };
+        T::from_sql_field(field, raw.as_deref())
+    }
+
+    /// Convert value of the column with the given name.
+    /// Exact match is preferred, otherwise names are compared case-insensitively,
+    /// as unquoted identifiers are returned in upper case.
+    pub fn get_by_name<T: FromSql>(&self, name: &str) -> Result<T, FromSqlError> {
+        let index = self
+            .schema
+            .iter()
+            .position(|f| f.name == name)
+            .or_else(|| {
+                self.schema
+                    .iter()
+                    .position(|f| f.name.eq_ignore_ascii_case(name))
+            })
+            .ok_or_else(|| FromSqlError::UnknownColumn(name.to_owned()))?;
+        self.get(index)
+    }
+}
+
+impl JsonResult {
+    /// Rows of the result, empty if the value is not an array of arrays
+    pub fn rows(&self) -> impl Iterator<Item = Row<'_>> {
+        self.rows_with(false)
+    }
+
+    /// Same as [`JsonResult::rows`], but values can only be read into types which accept
+    /// the column type, eg `TEXT` into `String` but not `NUMBER`.
+    /// Without it conversion is best-effort and succeeds whenever the value parses.
+    pub fn strict_rows(&self) -> impl Iterator<Item = Row<'_>> {
+        self.rows_with(true)
+    }
+
+    fn rows_with(&self, strict: bool) -> impl Iterator<Item = Row<'_>> {
+        self.value
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(Value::as_array)
+            .map(move |values| Row {
+                values,
+                schema: &self.schema,
+                strict,
+            })
+    }
+}
@@ -0,0 +1,183 @@
+//! [`tracing`](https://docs.rs/tracing) spans of logins and queries with the `tracing` feature,
+//! without it [`Span`] is zero-sized and every call compiles to nothing.
+//!
+//! Spans carry identifiers and timing only: the account and user of a login, the request,
+//! sequence and query ids of a query. Request payloads are never recorded, so passwords,
+//! tokens and bound parameters don't end up in traces.
+//!
+//! ```rust
+//! # #[cfg(all(feature = "tracing", feature = "test-util"))]
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), snowflake_api::SnowflakeApiError> {
+//! use std::sync::{Arc, Mutex};
+//!
+//! use serde_json::json;
+//! use snowflake_api::transport::MockTransport;
+//! use snowflake_api::{AuthArgs, AuthType, PasswordArgs, SnowflakeApiBuilder};
+//! use tracing::field::{Field, Visit};
+//! use tracing::span::{Attributes, Id, Record};
+//! use tracing::{Event, Metadata};
+//!
+//! /// Records span names and every field of spans and events
+//! #[derive(Default)]
+//! struct Recorder {
+//!     spans: Mutex<Vec<&'static str>>,
+//!     fields: Mutex<Vec<String>>,
+//! }
+//!
+//! impl Visit for &Recorder {
+//!     fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+//!         self.fields.lock().unwrap().push(format!("{field}={value:?}"));
+//!     }
+//! }
+//!
+//! struct Subscriber(Arc<Recorder>);
+//!
+//! impl tracing::Subscriber for Subscriber {
+//!     fn enabled(&self, _: &Metadata<'_>) -> bool {
+//!         true
+//!     }
+//!     fn new_span(&self, span: &Attributes<'_>) -> Id {
+//!         let mut spans = self.0.spans.lock().unwrap();
+//!         spans.push(span.metadata().name());
+//!         span.record(&mut &*self.0);
+//!         Id::from_u64(spans.len() as u64)
+//!     }
+//!     fn record(&self, _: &Id, values: &Record<'_>) {
+//!         values.record(&mut &*self.0);
+//!     }
+//!     fn record_follows_from(&self, _: &Id, _: &Id) {}
+//!     fn event(&self, event: &Event<'_>) {
+//!         event.record(&mut &*self.0);
+//!     }
+//!     fn enter(&self, _: &Id) {}
+//!     fn exit(&self, _: &Id) {}
+//! }
+//!
+//! let recorder = Arc::new(Recorder::default());
+//! let _guard = tracing::subscriber::set_default(Subscriber(recorder.clone()));
+//!
+//! let mock = MockTransport::new();
+//! mock.push_login();
+//! for query_id in ["01b2-first", "01b2-second"] {
+//!     let mut result = MockTransport::query_result(json!([]), json!([]));
+//!     result["data"]["queryId"] = json!(query_id);
+//!     mock.push_json("/queries/v1/query-request", &result);
+//! }
+//! let auth = AuthArgs {
+//!     auth_type: AuthType::Password(PasswordArgs {
+//!         password: "hunter2".to_owned(),
+//!         passcode: None,
+//!     }),
+//!     ..AuthArgs::for_tests()
+//! };
+//! let api = SnowflakeApiBuilder::new(auth).with_transport(mock).build()?;
+//! api.exec("SELECT 1").await?;
+//! api.exec("SELECT 2").await?;
+//!
+//! let spans = recorder.spans.lock().unwrap();
+//! assert_eq!(spans.iter().filter(|s| **s == "snowflake.login").count(), 1);
+//! assert_eq!(spans.iter().filter(|s| **s == "snowflake.query").count(), 2);
+//!
+//! let fields = recorder.fields.lock().unwrap();
+//! assert!(fields.iter().any(|f| f == "query_id=\"01b2-first\""));
+//! assert!(fields.iter().any(|f| f == "query_id=\"01b2-second\""));
+//! assert!(fields.iter().any(|f| f == "sequence_id=2"));
+//! assert!(fields.iter().any(|f| f.starts_with("elapsed_ms=")));
+//! assert!(!fields.iter().any(|f| f.contains("hunter2") || f.contains("session-token")));
+//! # Ok(())
+//! # }
+//! # #[cfg(not(all(feature = "tracing", feature = "test-util")))]
+//! # fn main() {}
+//! ```
+
+use std::fmt::Display;
+use std::future::Future;
+
+use uuid::Uuid;
+
+/// Span of a login or a query, finished with an event of its duration and outcome
+pub(crate) struct Span {
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+    #[cfg(feature = "tracing")]
+    start: std::time::Instant,
+}
+
+impl Span {
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    pub(crate) fn login(account: &str, user: &str) -> Self {
+        Span {
+            #[cfg(feature = "tracing")]
+            span: tracing::info_span!(
+                "snowflake.login",
+                account,
+                user,
+                elapsed_ms = tracing::field::Empty
+            ),
+            #[cfg(feature = "tracing")]
+            start: std::time::Instant::now(),
+        }
+    }
+
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    pub(crate) fn query(request_id: Uuid) -> Self {
+        Span {
+            #[cfg(feature = "tracing")]
+            span: tracing::info_span!(
+                "snowflake.query",
+                %request_id,
+                sequence_id = tracing::field::Empty,
+                query_id = tracing::field::Empty,
+                elapsed_ms = tracing::field::Empty,
+            ),
+            #[cfg(feature = "tracing")]
+            start: std::time::Instant::now(),
+        }
+    }
+
+    /// Runs `fut` inside the span
+    #[cfg_attr(not(feature = "tracing"), allow(clippy::unused_self))]
+    pub(crate) fn instrument<F: Future>(&self, fut: F) -> impl Future<Output = F::Output> {
+        #[cfg(feature = "tracing")]
+        return tracing::Instrument::instrument(fut, self.span.clone());
+        #[cfg(not(feature = "tracing"))]
+        fut
+    }
+
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables, clippy::unused_self))]
+    pub(crate) fn record_sequence_id(&self, sequence_id: u64) {
+        #[cfg(feature = "tracing")]
+        self.span.record("sequence_id", sequence_id);
+    }
+
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables, clippy::unused_self))]
+    pub(crate) fn record_query_id(&self, query_id: &str) {
+        #[cfg(feature = "tracing")]
+        self.span.record("query_id", query_id);
+    }
+
+    /// Records how long the spanned work took and the error it failed with
+    #[cfg_attr(not(feature = "tracing"), allow(clippy::unused_self))]
+    pub(crate) fn finish<T, E: Display>(self, result: Result<T, E>) -> Result<T, E> {
+        #[cfg(feature = "tracing")]
+        {
+            let elapsed_ms = u64::try_from(self.start.elapsed().as_millis()).unwrap_or(u64::MAX);
+            self.span.record("elapsed_ms", elapsed_ms);
+            match &result {
+                Ok(_) => tracing::debug!(parent: &self.span, elapsed_ms, "finished"),
+                Err(e) => tracing::warn!(parent: &self.span, elapsed_ms, error = %e, "failed"),
+            }
+        }
+        result
+    }
+
+    /// Runs `fut` inside the span and finishes it
+    pub(crate) async fn run<T, E: Display>(
+        self,
+        fut: impl Future<Output = Result<T, E>>,
+    ) -> Result<T, E> {
+        let result = self.instrument(fut).await;
+        self.finish(result)
+    }
+}
@@ -0,0 +1,260 @@
+//! HTTP layer of the [`crate::connection::Connection`].
+//!
+//! Requests are fully prepared by the connection, the transport only sends them and returns
+//! the raw response. The default transport is the `reqwest` client with its middleware,
+//! tests can substitute it to serve canned responses, see `MockTransport` with the `test-util` feature.
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use flate2::read::GzDecoder;
+use reqwest::header::{self, HeaderMap};
+use reqwest::{Method, StatusCode};
+use reqwest_middleware::ClientWithMiddleware;
+use url::Url;
+
+use crate::connection::ConnectionError;
+
+/// Request as it is sent to Snowflake
+#[derive(Debug, Clone)]
+pub struct TransportRequest {
+    pub method: Method,
+    pub url: Url,
+    pub headers: HeaderMap,
+    /// JSON payload, `None` for `GET` requests
+    pub body: Option<Bytes>,
+}
+
+impl TransportRequest {
+    /// Payload parsed as JSON, eg to assert what was sent.
+    /// Gzipped payloads are decompressed first.
+    pub fn json_body(&self) -> Option<serde_json::Value> {
+        let body = self.body.as_ref()?;
+        if self.is_gzipped() {
+            serde_json::from_reader(GzDecoder::new(body.as_ref())).ok()
+        } else {
+            serde_json::from_slice(body).ok()
+        }
+    }
+
+    /// Whether the payload is sent with `Content-Encoding: gzip`
+    pub fn is_gzipped(&self) -> bool {
+        self.headers
+            .get(header::CONTENT_ENCODING)
+            .is_some_and(|v| v == "gzip")
+    }
+}
+
+/// Raw response, Snowflake reports most errors in the body of successful responses
+#[derive(Debug, Clone)]
+pub struct TransportResponse {
+    pub status: StatusCode,
+    pub body: Bytes,
+}
+
+#[async_trait]
+pub trait Transport: Send + Sync {
+    async fn send(&self, request: TransportRequest) -> Result<TransportResponse, ConnectionError>;
+}
+
+#[async_trait]
+impl Transport for ClientWithMiddleware {
+    async fn send(&self, request: TransportRequest) -> Result<TransportResponse, ConnectionError> {
+        let mut builder = self
+            .request(request.method, request.url)
+            .headers(request.headers);
+        if let Some(body) = request.body {
+            builder = builder.body(body);
+        }
+
+        let resp = builder.send().await?;
+        let status = resp.status();
+        let body = resp.bytes().await?;
+        Ok(TransportResponse { status, body })
+    }
+}
+
+#[cfg(any(test, feature = "test-util"))]
+pub use mock::MockTransport;
+
+#[cfg(any(test, feature = "test-util"))]
+mod mock {
+    use std::collections::{HashMap, VecDeque};
+    use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+    use async_trait::async_trait;
+    use bytes::Bytes;
+    use reqwest::StatusCode;
+
+    use super::{Transport, TransportRequest, TransportResponse};
+    use crate::connection::ConnectionError;
+
+    /// Transport which serves queued responses and records requests.
+    /// Clones share the same queues, so one can be given to the client while the test keeps the other.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    /// use snowflake_api::transport::MockTransport;
+    /// use snowflake_api::{AuthArgs, QueryResult, SnowflakeApiBuilder};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), snowflake_api::SnowflakeApiError> {
+    /// let mock = MockTransport::new();
+    /// mock.push_login();
+    /// mock.push_query_result(
+    ///     json!([{ "name": "ONE", "type": "fixed", "scale": 0, "precision": 1, "nullable": false }]),
+    ///     json!([["1"]]),
+    /// );
+    ///
+    /// let auth = AuthArgs {
+    ///     warehouse: Some("WH".to_owned()),
+    ///     ..AuthArgs::for_tests()
+    /// };
+    /// let api = SnowflakeApiBuilder::new(auth)
+    ///     .with_transport(mock.clone())
+    ///     .build()?;
+    ///
+    /// let QueryResult::Json(result) = api.exec("SELECT 1 AS ONE").await? else {
+    ///     panic!("expected JSON result");
+    /// };
+    /// assert_eq!(result.value, json!([["1"]]));
+    ///
+    /// let requests = mock.requests();
+    /// assert_eq!(requests.len(), 2);
+    ///
+    /// let login = &requests[0];
+    /// assert!(login.url.query_pairs().any(|(k, v)| k == "warehouse" && v == "WH"));
+    /// let body = login.json_body().unwrap();
+    /// assert_eq!(body["data"]["LOGIN_NAME"], "USER");
+    /// assert_eq!(body["data"]["PASSWORD"], "password");
+    ///
+    /// let query = &requests[1];
+    /// assert_eq!(query.url.path(), "/queries/v1/query-request");
+    /// assert_eq!(query.headers["authorization"], r#"Snowflake Token="session-token""#);
+    /// assert_eq!(query.json_body().unwrap()["sqlText"], "SELECT 1 AS ONE");
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Chunks of large results are downloaded from their own URLs, which are mocked the same way,
+    /// see [`FetchOptions`](crate::FetchOptions).
+    #[derive(Debug, Clone, Default)]
+    pub struct MockTransport {
+        state: Arc<Mutex<MockState>>,
+    }
+
+    #[derive(Debug, Default)]
+    struct MockState {
+        responses: HashMap<String, VecDeque<TransportResponse>>,
+        requests: Vec<TransportRequest>,
+    }
+
+    impl MockTransport {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Queue a response for requests to the URL path, eg `/session/v1/login-request`.
+        /// Responses for the same path are served in the order they were queued.
+        pub fn push_response(&self, path: &str, response: TransportResponse) {
+            self.state()
+                .responses
+                .entry(path.to_owned())
+                .or_default()
+                .push_back(response);
+        }
+
+        /// Queue a successful JSON response for requests to the URL path
+        pub fn push_json(&self, path: &str, body: &serde_json::Value) {
+            self.push_response(
+                path,
+                TransportResponse {
+                    status: StatusCode::OK,
+                    body: Bytes::from(body.to_string()),
+                },
+            );
+        }
+
+        /// Queue a successful login response, which starts a session with the session token
+        /// `session-token` valid for an hour and the master token `master-token`
+        pub fn push_login(&self) {
+            self.push_json(
+                "/session/v1/login-request",
+                &serde_json::json!({
+                    "code": null,
+                    "message": null,
+                    "success": true,
+                    "data": {
+                        "sessionId": 1,
+                        "token": "session-token",
+                        "masterToken": "master-token",
+                        "serverVersion": "8.0.0",
+                        "sessionInfo": { "roleName": "PUBLIC" },
+                        "validityInSeconds": 3600,
+                        "masterValidityInSeconds": 14400
+                    }
+                }),
+            );
+        }
+
+        /// Body of a successful response of the query `01b2c3d4` with the JSON `rowset`,
+        /// eg to add `chunks` or change the `queryId` before queuing it with [`MockTransport::push_json`]
+        pub fn query_result(
+            rowtype: serde_json::Value,
+            rowset: serde_json::Value,
+        ) -> serde_json::Value {
+            let returned = rowset.as_array().map_or(0, Vec::len);
+            let mut body = serde_json::json!({
+                "code": null,
+                "message": null,
+                "success": true,
+                "data": {
+                    "parameters": [],
+                    "total": returned,
+                    "returned": returned,
+                    "queryId": "01b2c3d4",
+                    "finalRoleName": "PUBLIC",
+                    "statementTypeId": 4096,
+                    "version": 1
+                }
+            });
+            body["data"]["rowtype"] = rowtype;
+            body["data"]["rowset"] = rowset;
+            body
+        }
+
+        /// Queue a successful response to `/queries/v1/query-request` with the JSON `rowset`,
+        /// see [`MockTransport::query_result`]
+        pub fn push_query_result(&self, rowtype: serde_json::Value, rowset: serde_json::Value) {
+            self.push_json(
+                "/queries/v1/query-request",
+                &Self::query_result(rowtype, rowset),
+            );
+        }
+
+        /// Requests received so far, in the order they were sent
+        pub fn requests(&self) -> Vec<TransportRequest> {
+            self.state().requests.clone()
+        }
+
+        fn state(&self) -> MutexGuard<'_, MockState> {
+            self.state.lock().unwrap_or_else(PoisonError::into_inner)
+        }
+    }
+
+    #[async_trait]
+    impl Transport for MockTransport {
+        async fn send(
+            &self,
+            request: TransportRequest,
+        ) -> Result<TransportResponse, ConnectionError> {
+            let mut state = self.state();
+            let path = request.url.path().to_owned();
+            state.requests.push(request);
+            state
+                .responses
+                .get_mut(&path)
+                .and_then(VecDeque::pop_front)
+                .ok_or(ConnectionError::NoMockedResponse(path))
+        }
+    }
+}